@@ -0,0 +1,176 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::parse_macro_input;
+use syn::Data;
+use syn::DeriveInput;
+use syn::Error;
+use syn::Fields;
+use syn::Lit;
+use syn::LitStr;
+use syn::Meta;
+use syn::MetaNameValue;
+use syn::NestedMeta;
+
+/// Implements `HotkeyActions` for an enum of unit variants.
+///
+/// Each variant can be annotated with `#[hotkey(...)]` containing
+/// * strings such as `"ctrl+S", "F2"`: the default hotkeys of the action,
+/// * `name = "Save"`: the name shown to the user,
+/// * `description = "Save the current file"`: a longer explanation,
+/// * `category = "File"`: the group the action is shown in.
+#[proc_macro_derive(HotkeyActions, attributes(hotkey))]
+pub fn derive_hotkey_actions(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+struct Variant {
+    ident: syn::Ident,
+    hotkeys: Vec<LitStr>,
+    name: Option<LitStr>,
+    description: Option<LitStr>,
+    category: Option<LitStr>,
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "HotkeyActions can only be derived for enums",
+            ))
+        }
+    };
+    let variants = data
+        .variants
+        .iter()
+        .map(|variant| {
+            if !matches!(variant.fields, Fields::Unit) {
+                return Err(Error::new_spanned(
+                    variant,
+                    "HotkeyActions can only be derived for enums with unit variants",
+                ));
+            }
+            parse_variant(variant)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let all = variants.iter().map(|variant| &variant.ident);
+    let hotkeys = variants.iter().map(|variant| {
+        let ident = &variant.ident;
+        let hotkeys = &variant.hotkeys;
+        quote! { Self::#ident => ::std::vec![#(#hotkeys),*], }
+    });
+    let names = variants
+        .iter()
+        .map(|variant| optional_match_arm(&variant.ident, &variant.name));
+    let descriptions = variants
+        .iter()
+        .map(|variant| optional_match_arm(&variant.ident, &variant.description));
+    let categories = variants
+        .iter()
+        .map(|variant| optional_match_arm(&variant.ident, &variant.category));
+    Ok(quote! {
+        impl #impl_generics ::bevy_hotkey_config::hotkey_actions::HotkeyActions for #ident #ty_generics #where_clause {
+            fn all() -> ::std::vec::Vec<Self> {
+                ::std::vec![#(Self::#all),*]
+            }
+
+            fn default_hotkeys(&self) -> ::std::vec::Vec<&'static str> {
+                match *self {
+                    #(#hotkeys)*
+                }
+            }
+
+            fn display_name(&self) -> ::std::option::Option<&'static str> {
+                match *self {
+                    #(#names)*
+                }
+            }
+
+            fn description(&self) -> ::std::option::Option<&'static str> {
+                match *self {
+                    #(#descriptions)*
+                }
+            }
+
+            fn category(&self) -> ::std::option::Option<&'static str> {
+                match *self {
+                    #(#categories)*
+                }
+            }
+        }
+    })
+}
+
+fn optional_match_arm(ident: &syn::Ident, value: &Option<LitStr>) -> TokenStream2 {
+    match value {
+        Some(value) => quote! { Self::#ident => ::std::option::Option::Some(#value), },
+        None => quote! { Self::#ident => ::std::option::Option::None, },
+    }
+}
+
+fn parse_variant(variant: &syn::Variant) -> Result<Variant, Error> {
+    let mut parsed = Variant {
+        ident: variant.ident.clone(),
+        hotkeys: vec![],
+        name: None,
+        description: None,
+        category: None,
+    };
+    for attr in variant
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("hotkey"))
+    {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => {
+                return Err(Error::new_spanned(
+                    meta,
+                    "expected #[hotkey(\"...\")] or #[hotkey(name = \"...\")]",
+                ))
+            }
+        };
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Lit(Lit::Str(string)) => parsed.hotkeys.push(string),
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(string),
+                    ..
+                })) => {
+                    let field = if path.is_ident("name") {
+                        &mut parsed.name
+                    } else if path.is_ident("description") {
+                        &mut parsed.description
+                    } else if path.is_ident("category") {
+                        &mut parsed.category
+                    } else {
+                        return Err(Error::new_spanned(
+                            path,
+                            "expected `name`, `description` or `category`",
+                        ));
+                    };
+                    if field.is_some() {
+                        return Err(Error::new_spanned(path, "duplicate hotkey attribute"));
+                    }
+                    *field = Some(string);
+                }
+                nested => {
+                    return Err(Error::new_spanned(
+                        nested,
+                        "expected a hotkey string or `name = \"...\"`",
+                    ))
+                }
+            }
+        }
+    }
+    Ok(parsed)
+}
@@ -1,33 +1,26 @@
 use bevy::prelude::App;
-use bevy::prelude::KeyCode;
 use bevy::prelude::Res;
 use bevy::DefaultPlugins;
-use hotkey_plugin::hotkey_config::HotkeyConfig;
-use hotkey_plugin::hotkey_plugin::HotkeyPlugin;
-use hotkey_plugin::hotkey_states::HotkeyStates;
-use hotkey_plugin::modifier::Modifier;
+use bevy_hotkey_config::hotkey_actions::HotkeyActions;
+use bevy_hotkey_config::hotkey_config::HotkeyConfig;
+use bevy_hotkey_config::hotkey_plugin::HotkeyPlugin;
+use bevy_hotkey_config::hotkey_states::HotkeyStates;
 
-#[derive(Clone, PartialEq, Eq, Hash)]
+#[derive(Clone, PartialEq, Eq, Hash, HotkeyActions)]
 enum GameAction {
+    #[hotkey("A", "Left", "ctrl+A")]
     WalkLeft,
+    #[hotkey("D", "Right", "ctrl+D")]
     WalkRight,
+    #[hotkey("W", "Up")]
     Jump,
+    #[hotkey("S", "Down")]
     Duck,
 }
 
 fn main() {
     let mut app = App::new();
-    let mut config = HotkeyConfig::<GameAction>::empty();
-    config.insert_normal(GameAction::WalkLeft, KeyCode::A);
-    config.insert_normal(GameAction::WalkLeft, KeyCode::Left);
-    config.insert_with_modifiers(GameAction::WalkLeft, KeyCode::A, &[Modifier::Control]);
-    config.insert_normal(GameAction::WalkRight, KeyCode::D);
-    config.insert_normal(GameAction::WalkRight, KeyCode::Right);
-    config.insert_with_modifiers(GameAction::WalkRight, KeyCode::D, &[Modifier::Control]);
-    config.insert_normal(GameAction::Jump, KeyCode::W);
-    config.insert_normal(GameAction::Jump, KeyCode::Up);
-    config.insert_normal(GameAction::Duck, KeyCode::S);
-    config.insert_normal(GameAction::Duck, KeyCode::Down);
+    let config = HotkeyConfig::<GameAction>::from_defaults().unwrap();
 
     app.add_plugins(DefaultPlugins)
         .add_plugin(HotkeyPlugin::<GameAction>::new(config))
@@ -1,11 +1,12 @@
 use bevy::prelude::App;
+use bevy::prelude::AssetServer;
 use bevy::prelude::Res;
 use bevy::DefaultPlugins;
-use hotkey_plugin::hotkey_plugin::HotkeyPlugin;
-use hotkey_plugin::hotkey_states::HotkeyStates;
+use bevy_hotkey_config::hotkey_config::HotkeyConfig;
+use bevy_hotkey_config::hotkey_plugin::HotkeyPlugin;
+use bevy_hotkey_config::hotkey_states::HotkeyStates;
 use serde::Deserialize;
 use serde::Serialize;
-use serde_json;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 enum GameAction {
@@ -18,16 +19,23 @@ enum GameAction {
 fn main() {
     let mut app = App::new();
 
-    let config =
-        serde_json::from_reader(std::fs::File::open("assets/hotkeys.json").unwrap()).unwrap();
-
+    // The hotkeys are loaded from assets/game.hotkeys.json. Changes to the file
+    // are picked up while the app is running.
     app.add_plugins(DefaultPlugins)
-        .add_plugin(HotkeyPlugin::<GameAction>::new(config))
+        .add_plugin(
+            HotkeyPlugin::<GameAction>::new(HotkeyConfig::empty())
+                .load_as_asset("game.hotkeys.json"),
+        )
+        .add_startup_system(watch_for_changes_system)
         .add_system(input_system);
 
     app.run();
 }
 
+fn watch_for_changes_system(asset_server: Res<AssetServer>) {
+    asset_server.watch_for_changes().unwrap();
+}
+
 fn input_system(hotkeys: Res<HotkeyStates<GameAction>>) {
     if hotkeys.repeated(GameAction::WalkLeft) {
         println!("Walking to the left");
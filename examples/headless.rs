@@ -0,0 +1,35 @@
+use bevy::input::Input;
+use bevy::prelude::App;
+use bevy::prelude::KeyCode;
+use bevy::MinimalPlugins;
+use bevy_hotkey_config::hotkey_actions::HotkeyActions;
+use bevy_hotkey_config::hotkey_config::HotkeyConfig;
+use bevy_hotkey_config::hotkey_plugin::HotkeyPlugin;
+use bevy_hotkey_config::hotkey_states::HotkeyStates;
+
+#[derive(Clone, PartialEq, Eq, Hash, HotkeyActions)]
+enum GameAction {
+    #[hotkey("W")]
+    Jump,
+}
+
+// Runs without a window or input plugins, e.g. in CI, by pressing keys directly.
+fn main() {
+    let mut app = App::new();
+    let config = HotkeyConfig::<GameAction>::from_defaults().unwrap();
+
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(HotkeyPlugin::<GameAction>::new(config).with_headless_input());
+
+    app.world
+        .get_resource_mut::<Input<KeyCode>>()
+        .unwrap()
+        .press(KeyCode::W);
+    app.update();
+    let hotkeys = app
+        .world
+        .get_resource::<HotkeyStates<GameAction>>()
+        .unwrap();
+    assert!(hotkeys.just_pressed(GameAction::Jump));
+    println!("Jumped without a window");
+}
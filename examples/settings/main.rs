@@ -1,11 +1,14 @@
 use bevy::prelude::*;
-use hotkey_plugin::action::Action;
-use hotkey_plugin::hotkey_listener::HotkeyListener;
-use hotkey_plugin::hotkey_plugin::HotkeyPlugin;
-use hotkey_plugin::hotkey_states::HotkeyStates;
+use bevy_hotkey_config::action::Action;
+use bevy_hotkey_config::action_registry::ActionRegistry;
+use bevy_hotkey_config::hotkey_actions::HotkeyActions;
+use bevy_hotkey_config::hotkey_config::HotkeyConfig;
+use bevy_hotkey_config::hotkey_listener::HotkeyListener;
+use bevy_hotkey_config::hotkey_plugin::HotkeyPlugin;
+use bevy_hotkey_config::hotkey_states::HotkeyStates;
+use bevy_hotkey_config::persistence::ConfigFormat;
 use serde::Deserialize;
 use serde::Serialize;
-use serde_json;
 use ui::change_button_text_system;
 use ui::setup_ui;
 use ui::ApplySettingsButton;
@@ -13,33 +16,31 @@ use ui::HotkeyButton;
 
 mod ui;
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug, HotkeyActions)]
 pub enum GameAction {
+    #[hotkey(name = "Walk left")]
     WalkLeft,
+    #[hotkey(name = "Walk right")]
     WalkRight,
     Jump,
     Duck,
 }
 
-impl GameAction {
-    fn get_name(&self) -> &str {
-        match self {
-            GameAction::WalkLeft => "Walk left",
-            GameAction::WalkRight => "Walk right",
-            GameAction::Jump => "Jump",
-            GameAction::Duck => "Duck",
-        }
-    }
-}
-
 fn main() {
-    let config = serde_json::from_str(include_str!("../../assets/settings.json")).unwrap();
+    let config = HotkeyConfig::load_from_str(
+        include_str!("../../assets/game.hotkeys.json"),
+        ConfigFormat::Json,
+    )
+    .unwrap();
     App::new()
         .add_plugin(
             HotkeyPlugin::<GameAction>::new(config)
-                .allow_modification(Action::Key(KeyCode::Escape), Action::Key(KeyCode::Back)),
+                .allow_modification(Action::Key(KeyCode::Escape), Action::Key(KeyCode::Back))
+                .persist("user_hotkeys.json", ConfigFormat::Json)
+                .warn_unbound_actions(),
         )
         .add_plugins(DefaultPlugins)
+        .insert_resource(ActionRegistry::<GameAction>::from_actions())
         .add_startup_system(setup_ui)
         .add_system(select_hotkeys_system)
         .add_system(apply_hotkeys_system)
@@ -48,6 +49,7 @@ fn main() {
         .run();
 }
 
+#[allow(clippy::type_complexity)]
 fn select_hotkeys_system(
     mut interaction_query: Query<
         (&Interaction, &HotkeyButton),
@@ -62,6 +64,7 @@ fn select_hotkeys_system(
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn apply_hotkeys_system(
     interaction_query: Query<
         &Interaction,
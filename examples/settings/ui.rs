@@ -1,6 +1,7 @@
 use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
-use hotkey_plugin::hotkey_config::HotkeyConfig;
+use bevy_hotkey_config::action_registry::ActionRegistry;
+use bevy_hotkey_config::hotkey_config::HotkeyConfig;
 
 use crate::GameAction;
 
@@ -60,7 +61,7 @@ fn spawn_text<'w, 's, 'a, 'b>(
     font: &Handle<Font>,
     size: Option<Size<Val>>,
 ) -> EntityCommands<'w, 's, 'a> {
-    let size = size.unwrap_or(Size::default());
+    let size = size.unwrap_or_default();
     parent.spawn_bundle(TextBundle {
         style: Style {
             size,
@@ -79,7 +80,11 @@ fn spawn_text<'w, 's, 'a, 'b>(
     })
 }
 
-pub fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+pub fn setup_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    registry: Res<ActionRegistry<GameAction>>,
+) {
     let font = asset_server.load("fonts/FiraSans-Bold.ttf");
     commands.spawn_bundle(UiCameraBundle::default());
     commands
@@ -94,12 +99,7 @@ pub fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
             ..Default::default()
         })
         .with_children(|parent| {
-            for action in [
-                GameAction::WalkLeft,
-                GameAction::WalkRight,
-                GameAction::Jump,
-                GameAction::Duck,
-            ] {
+            for (action, info) in registry.sorted() {
                 parent
                     .spawn_bundle(NodeBundle {
                         style: Style {
@@ -114,19 +114,19 @@ pub fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                     .with_children(|parent| {
                         spawn_text(
                             parent,
-                            action.get_name(),
+                            &info.name,
                             &font,
                             Some(Size::new(Val::Px(200.0), Val::Px(65.0))),
                         );
                         for num in 0..NUM_HOTKEYS_TO_SHOW {
                             // Spawn with empty text, this is going to get set in change_button_text_system immediately anyways
-                            spawn_button(parent, "".into(), &font).insert(HotkeyButton {
+                            spawn_button(parent, "", &font).insert(HotkeyButton {
                                 action: action.clone(),
                                 num,
                             });
                         }
                     });
             }
-            spawn_button(parent, "Apply".into(), &font).insert(ApplySettingsButton);
+            spawn_button(parent, "Apply", &font).insert(ApplySettingsButton);
         });
 }
@@ -1,13 +1,213 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
 use bevy::prelude::KeyCode;
 use bevy::prelude::MouseButton;
+use serde::de::value::StrDeserializer;
+use serde::de::value::U32Deserializer;
+use serde::de::IntoDeserializer;
 use serde::Deserialize;
 use serde::Serialize;
 
+use super::mouse_motion_action::MouseMotionAction;
 use super::mouse_wheel_action::MouseWheelAction;
+use super::touch_action::TouchAction;
+use crate::config;
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub enum Action {
     Button(MouseButton),
     Scroll(MouseWheelAction),
     Key(KeyCode),
+    /// The mouse moved further than the `MotionSettings::threshold` within a frame.
+    Motion(MouseMotionAction),
+    /// The mouse moved further than the `MotionSettings::drag_threshold`
+    /// while the button was held.
+    Drag(MouseButton),
+    /// A physical key, independent of the keyboard layout.
+    ScanCode(u32),
+    Touch(TouchAction),
+    /// A typed character, independent of the keys needed to type it.
+    Char(char),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseHotkeyError(pub(crate) String);
+
+impl Display for ParseHotkeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid hotkey: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseHotkeyError {}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Button(MouseButton::Other(num)) => write!(f, "Mouse{}", num),
+            Action::Button(button) => write!(f, "Mouse{:?}", button),
+            Action::Scroll(action) => write!(f, "Scroll{:?}", action),
+            Action::Key(key) => write!(f, "{:?}", key),
+            Action::Motion(action) => write!(f, "MouseMove{:?}", action),
+            Action::Drag(button) => write!(f, "Drag{}", Action::Button(*button)),
+            Action::ScanCode(scan_code) => write!(f, "Scan{}", scan_code),
+            Action::Touch(TouchAction::Tap) => write!(f, "Tap"),
+            Action::Touch(TouchAction::Fingers(fingers)) => write!(f, "Tap{}", fingers),
+            Action::Touch(TouchAction::Region(region)) => write!(f, "Touch{}", region),
+            // Characters which would not survive parsing are written as their code.
+            Action::Char(c) if c.is_whitespace() || c.to_string() == config::HOTKEY_SEPARATOR => {
+                write!(f, "Char#{}", *c as u32)
+            }
+            Action::Char(c) => write!(f, "Char{}", c),
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = ParseHotkeyError;
+
+    /// Names are case-insensitive, except for the character of `Char` and the
+    /// name of a touch region.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "scrollup" => return Ok(Action::Scroll(MouseWheelAction::Up)),
+            "scrolldown" => return Ok(Action::Scroll(MouseWheelAction::Down)),
+            "scrollleft" => return Ok(Action::Scroll(MouseWheelAction::Left)),
+            "scrollright" => return Ok(Action::Scroll(MouseWheelAction::Right)),
+            "mouseleft" => return Ok(Action::Button(MouseButton::Left)),
+            "mouseright" => return Ok(Action::Button(MouseButton::Right)),
+            "mousemiddle" => return Ok(Action::Button(MouseButton::Middle)),
+            "mousemoveup" => return Ok(Action::Motion(MouseMotionAction::Up)),
+            "mousemovedown" => return Ok(Action::Motion(MouseMotionAction::Down)),
+            "mousemoveleft" => return Ok(Action::Motion(MouseMotionAction::Left)),
+            "mousemoveright" => return Ok(Action::Motion(MouseMotionAction::Right)),
+            "tap" => return Ok(Action::Touch(TouchAction::Tap)),
+            _ => {}
+        }
+        if let Some(Ok(Action::Button(button))) =
+            strip_prefix_ignore_case(s, "Drag").map(str::parse)
+        {
+            return Ok(Action::Drag(button));
+        }
+        if let Some(Ok(fingers)) = strip_prefix_ignore_case(s, "Tap").map(str::parse) {
+            return Ok(Action::Touch(TouchAction::Fingers(fingers)));
+        }
+        if let Some(region) =
+            strip_prefix_ignore_case(s, "Touch").filter(|region| !region.is_empty())
+        {
+            return Ok(Action::Touch(TouchAction::Region(region.into())));
+        }
+        if let Some(c) = strip_prefix_ignore_case(s, "Char") {
+            let mut chars = c.chars();
+            return match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(Action::Char(c)),
+                _ => c
+                    .strip_prefix('#')
+                    .and_then(|code| code.parse().ok())
+                    .and_then(char::from_u32)
+                    .map(Action::Char)
+                    .ok_or_else(|| ParseHotkeyError(s.into())),
+            };
+        }
+        if let Some(Ok(scan_code)) = strip_prefix_ignore_case(s, "Scan").map(str::parse) {
+            return Ok(Action::ScanCode(scan_code));
+        }
+        if let Some(Ok(num)) = strip_prefix_ignore_case(s, "Mouse").map(str::parse) {
+            return Ok(Action::Button(MouseButton::Other(num)));
+        }
+        // KeyCode only implements Deserialize, so parse the variant name through serde.
+        let deserializer: StrDeserializer<serde::de::value::Error> = s.into_deserializer();
+        KeyCode::deserialize(deserializer)
+            .ok()
+            .or_else(|| key_codes().find(|key| format!("{:?}", key).eq_ignore_ascii_case(s)))
+            .map(Action::Key)
+            .ok_or_else(|| ParseHotkeyError(s.into()))
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    s.get(..prefix.len())
+        .filter(|start| start.eq_ignore_ascii_case(prefix))
+        .map(|_| &s[prefix.len()..])
+}
+
+/// Every `KeyCode`, deserialized by variant index until the indices run out.
+fn key_codes() -> impl Iterator<Item = KeyCode> {
+    (0u32..).map_while(|index| {
+        let deserializer: U32Deserializer<serde::de::value::Error> = index.into_deserializer();
+        KeyCode::deserialize(deserializer).ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Action {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn names_are_case_insensitive() {
+        assert_eq!(parse("space"), Action::Key(KeyCode::Space));
+        assert_eq!(parse("NUMPAD0"), Action::Key(KeyCode::Numpad0));
+        assert_eq!(parse("SCROLLUP"), Action::Scroll(MouseWheelAction::Up));
+        assert_eq!(parse("mouseleft"), Action::Button(MouseButton::Left));
+        assert_eq!(parse("mouse4"), Action::Button(MouseButton::Other(4)));
+        assert_eq!(parse("dragmouseleft"), Action::Drag(MouseButton::Left));
+        assert_eq!(parse("scan17"), Action::ScanCode(17));
+        assert_eq!(parse("tap2"), Action::Touch(TouchAction::Fingers(2)));
+    }
+
+    #[test]
+    fn chars_and_regions_keep_their_case() {
+        assert_eq!(parse("chara"), Action::Char('a'));
+        assert_eq!(parse("CharA"), Action::Char('A'));
+        assert_eq!(
+            parse("TouchJump"),
+            Action::Touch(TouchAction::Region("Jump".into()))
+        );
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let actions = [
+            Action::Key(KeyCode::Space),
+            Action::Button(MouseButton::Right),
+            Action::Button(MouseButton::Other(4)),
+            Action::Scroll(MouseWheelAction::Left),
+            Action::Motion(MouseMotionAction::Down),
+            Action::Drag(MouseButton::Middle),
+            Action::ScanCode(17),
+            Action::Touch(TouchAction::Tap),
+            Action::Touch(TouchAction::Fingers(3)),
+            Action::Touch(TouchAction::Region("Jump".into())),
+            Action::Char('ä'),
+            Action::Char('+'),
+            Action::Char(' '),
+        ];
+        for action in actions {
+            assert_eq!(parse(&action.to_string()), action);
+        }
+    }
+
+    #[test]
+    fn separator_and_whitespace_chars_are_written_as_codes() {
+        assert_eq!(Action::Char('+').to_string(), "Char#43");
+        assert_eq!(Action::Char(' ').to_string(), "Char#32");
+        assert_eq!(parse("Char#43"), Action::Char('+'));
+    }
+
+    #[test]
+    fn invalid_names() {
+        for s in ["", "Foo", "Char", "Char#x", "Char#55296", "Touch", "ScanX"] {
+            assert_eq!(
+                s.parse::<Action>(),
+                Err(ParseHotkeyError(s.into())),
+                "{}",
+                s
+            );
+        }
+    }
 }
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hotkey_actions::HotkeyActions;
+
+/// What to show the user about an action in settings menus or help overlays.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActionInfo {
+    pub name: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    /// Actions are listed in ascending order.
+    pub order: i32,
+}
+
+impl ActionInfo {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            category: None,
+            order: 0,
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    pub fn with_order(mut self, order: i32) -> Self {
+        self.order = order;
+        self
+    }
+}
+
+/// Holds an `ActionInfo` for each action. Can be inserted as a resource
+/// to generate settings menus from.
+#[derive(Clone, Debug)]
+pub struct ActionRegistry<T: Eq + Hash + Clone> {
+    actions: HashMap<T, ActionInfo>,
+}
+
+impl<T: Eq + Hash + Clone> Default for ActionRegistry<T> {
+    fn default() -> Self {
+        Self {
+            actions: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Eq + Hash + Clone> ActionRegistry<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, name: T, info: ActionInfo) {
+        self.actions.insert(name, info);
+    }
+
+    pub fn with(mut self, name: T, info: ActionInfo) -> Self {
+        self.insert(name, info);
+        self
+    }
+
+    pub fn get(&self, name: &T) -> Option<&ActionInfo> {
+        self.actions.get(name)
+    }
+
+    pub fn get_mut(&mut self, name: &T) -> Option<&mut ActionInfo> {
+        self.actions.get_mut(name)
+    }
+
+    pub fn name(&self, name: &T) -> Option<&str> {
+        self.get(name).map(|info| info.name.as_str())
+    }
+
+    /// All actions ordered by `ActionInfo::order`, then by name.
+    pub fn sorted(&self) -> Vec<(&T, &ActionInfo)> {
+        let mut actions: Vec<_> = self.actions.iter().collect();
+        actions.sort_by(|(_, a), (_, b)| a.order.cmp(&b.order).then_with(|| a.name.cmp(&b.name)));
+        actions
+    }
+
+    /// The sorted actions grouped by category. The categories appear in the
+    /// order of their first action.
+    pub fn by_category(&self) -> Vec<ActionCategory<'_, T>> {
+        let mut categories: Vec<ActionCategory<T>> = vec![];
+        for (name, info) in self.sorted() {
+            let category = info.category.as_deref();
+            match categories.iter_mut().find(|other| other.name == category) {
+                Some(other) => other.actions.push((name, info)),
+                None => categories.push(ActionCategory {
+                    name: category,
+                    actions: vec![(name, info)],
+                }),
+            }
+        }
+        categories
+    }
+}
+
+pub struct ActionCategory<'a, T> {
+    /// `None` for actions without a category.
+    pub name: Option<&'a str>,
+    pub actions: Vec<(&'a T, &'a ActionInfo)>,
+}
+
+impl<T: HotkeyActions + Debug> ActionRegistry<T> {
+    /// Builds the registry from the `HotkeyActions` implementation. Actions
+    /// without a display name use their debug representation. The order
+    /// is the order of `HotkeyActions::all`.
+    pub fn from_actions() -> Self {
+        T::all()
+            .into_iter()
+            .enumerate()
+            .fold(Self::new(), |registry, (order, name)| {
+                let mut info = ActionInfo::new(
+                    name.display_name()
+                        .map(String::from)
+                        .unwrap_or_else(|| format!("{:?}", name)),
+                )
+                .with_order(order as i32);
+                info.description = name.description().map(String::from);
+                info.category = name.category().map(String::from);
+                registry.with(name, info)
+            })
+    }
+}
@@ -0,0 +1,223 @@
+use std::fmt::Write;
+use std::hash::Hash;
+
+use crate::action_registry::ActionCategory;
+use crate::action_registry::ActionRegistry;
+use crate::hotkey_config::HotkeyConfig;
+use crate::keyboard_layout::KeyboardLayout;
+
+// Heading for actions without a category if there are other categories.
+const OTHER_CATEGORY: &str = "Other";
+const UNBOUND: &str = "-";
+
+/// Renders the hotkeys of every action in the registry as a control
+/// reference, grouped by category. Actions missing from the registry
+/// are not listed.
+pub struct CheatSheet<'a, T: Eq + Hash + Clone> {
+    config: &'a HotkeyConfig<T>,
+    registry: &'a ActionRegistry<T>,
+    layout: Option<&'a KeyboardLayout>,
+    title: String,
+}
+
+impl<'a, T: Eq + Hash + Clone> CheatSheet<'a, T> {
+    pub fn new(config: &'a HotkeyConfig<T>, registry: &'a ActionRegistry<T>) -> Self {
+        Self {
+            config,
+            registry,
+            layout: None,
+            title: "Controls".into(),
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Show physical key bindings with the labels of the player's layout,
+    /// e.g. from `HotkeyStates::keyboard_layout`.
+    pub fn with_layout(mut self, layout: &'a KeyboardLayout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}", escape_markdown(&self.title));
+        for category in self.categories() {
+            if let Some(name) = category.name {
+                let _ = write!(out, "\n## {}\n", escape_markdown(name));
+            }
+            out.push_str("\n| Action | Hotkeys | Description |\n|---|---|---|\n");
+            for (name, info) in category.actions {
+                let hotkeys = self.hotkeys(name, code_span);
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} |",
+                    escape_markdown(&info.name),
+                    hotkeys,
+                    escape_markdown(info.description.as_deref().unwrap_or_default())
+                );
+            }
+        }
+        out
+    }
+
+    /// A standalone page without external resources.
+    pub fn to_html(&self) -> String {
+        let title = escape_html(&self.title);
+        let mut out = String::new();
+        let _ = write!(
+            out,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+             <style>\n\
+             body {{ font-family: sans-serif; margin: 2em; }}\n\
+             table {{ border-collapse: collapse; margin-bottom: 2em; }}\n\
+             th, td {{ border: 1px solid #ccc; padding: 0.3em 0.8em; text-align: left; }}\n\
+             kbd {{ border: 1px solid #aaa; border-radius: 3px; padding: 0 0.3em; }}\n\
+             </style>\n</head>\n<body>\n<h1>{title}</h1>\n",
+            title = title
+        );
+        for category in self.categories() {
+            if let Some(name) = category.name {
+                let _ = writeln!(out, "<h2>{}</h2>", escape_html(name));
+            }
+            out.push_str("<table>\n<tr><th>Action</th><th>Hotkeys</th><th>Description</th></tr>\n");
+            for (name, info) in category.actions {
+                let hotkeys =
+                    self.hotkeys(name, |hotkey| format!("<kbd>{}</kbd>", escape_html(hotkey)));
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    escape_html(&info.name),
+                    hotkeys,
+                    escape_html(info.description.as_deref().unwrap_or_default())
+                );
+            }
+            out.push_str("</table>\n");
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    fn categories(&self) -> Vec<ActionCategory<'a, T>> {
+        let mut categories = self.registry.by_category();
+        if categories.len() > 1 {
+            for category in categories.iter_mut() {
+                category.name = category.name.or(Some(OTHER_CATEGORY));
+            }
+        }
+        categories
+    }
+
+    fn hotkeys(&self, name: &T, format: impl Fn(&str) -> String) -> String {
+        let hotkeys: Vec<_> = self
+            .config
+            .get(name)
+            .into_iter()
+            .flat_map(|hotkeys| hotkeys.iter())
+            .map(|hotkey| match self.layout {
+                Some(layout) => format(&layout.label(hotkey)),
+                None => format(&hotkey.to_string()),
+            })
+            .collect();
+        if hotkeys.is_empty() {
+            UNBOUND.into()
+        } else {
+            hotkeys.join(", ")
+        }
+    }
+}
+
+// Every ASCII punctuation character can be escaped in Markdown, including
+// the backslash itself, so names are shown literally.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\n' => escaped.push(' '),
+            c if c.is_ascii_punctuation() => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Code spans are literal, but tables still split cells on unescaped pipes.
+// Hotkeys containing a backtick, such as the character binding Char`, need a longer fence.
+fn code_span(text: &str) -> String {
+    let text = text.replace('|', "\\|");
+    if text.contains('`') {
+        format!("`` {} ``", text)
+    } else {
+        format!("`{}`", text)
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action_registry::ActionInfo;
+
+    #[test]
+    fn escapes_markdown_punctuation() {
+        assert_eq!(escape_markdown("Toggle_Grid"), "Toggle\\_Grid");
+        assert_eq!(escape_markdown("*Debug*"), "\\*Debug\\*");
+        assert_eq!(escape_markdown("a\\|b"), "a\\\\\\|b");
+        assert_eq!(escape_markdown("two\nlines"), "two lines");
+        assert_eq!(escape_markdown("Jump höher"), "Jump höher");
+    }
+
+    #[test]
+    fn code_spans() {
+        assert_eq!(code_span("ctrl+S"), "`ctrl+S`");
+        assert_eq!(code_span("Char|"), "`Char\\|`");
+        assert_eq!(code_span("Char`"), "`` Char` ``");
+    }
+
+    #[test]
+    fn escapes_html() {
+        assert_eq!(
+            escape_html("<a href=\"x\">&</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn markdown_table() {
+        let mut config = HotkeyConfig::empty();
+        config.insert_parsed("Save", "ctrl+S").unwrap();
+        config.insert_parsed("Grid", "Char|").unwrap();
+        let registry = ActionRegistry::new()
+            .with("Save", ActionInfo::new("Save").with_order(0))
+            .with(
+                "Grid",
+                ActionInfo::new("Toggle_Grid")
+                    .with_description("Shows *all* cells")
+                    .with_order(1),
+            )
+            .with("Quit", ActionInfo::new("Quit").with_order(2));
+        let markdown = CheatSheet::new(&config, &registry)
+            .with_title("Controls #1")
+            .to_markdown();
+        assert_eq!(
+            markdown,
+            "# Controls \\#1\n\
+             \n| Action | Hotkeys | Description |\n|---|---|---|\n\
+             | Save | `ctrl+S` |  |\n\
+             | Toggle\\_Grid | `Char\\|` | Shows \\*all\\* cells |\n\
+             | Quit | - |  |\n"
+        );
+    }
+}
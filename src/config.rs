@@ -1,3 +1,9 @@
 pub const HOTKEY_SEPARATOR: &str = "+";
 pub const DEFAULT_REPEAT_INITIAL_DELAY: f32 = 0.3;
 pub const DEFAULT_REPEAT_DELAY: f32 = 0.13;
+pub const DEFAULT_PIXELS_PER_LINE: f32 = 20.0;
+pub const DEFAULT_SCROLL_THRESHOLD: f32 = 1.0;
+pub const DEFAULT_MOTION_THRESHOLD: f32 = 5.0;
+pub const DEFAULT_DRAG_THRESHOLD: f32 = 4.0;
+// Keeps zero or negative delays from repeating forever within a single frame.
+pub const MIN_REPEAT_DELAY: f32 = 0.001;
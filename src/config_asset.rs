@@ -0,0 +1,165 @@
+use std::hash::Hash;
+
+use bevy::asset::AddAsset;
+use bevy::asset::AssetEvent;
+use bevy::asset::AssetLoader;
+use bevy::asset::AssetServer;
+use bevy::asset::Assets;
+use bevy::asset::BoxedFuture;
+use bevy::asset::Handle;
+use bevy::asset::LoadContext;
+use bevy::asset::LoadedAsset;
+use bevy::log::error;
+use bevy::prelude::App;
+use bevy::prelude::EventReader;
+use bevy::prelude::Res;
+use bevy::prelude::ResMut;
+use bevy::reflect::TypeUuid;
+use bevy::utils::Uuid;
+use serde::de::DeserializeOwned;
+
+use crate::hotkey_config::HotkeyConfig;
+use crate::hotkey_states::HotkeyStates;
+use crate::migration::ConfigDocument;
+use crate::migration::HotkeyMigrations;
+use crate::persistence::parse_str;
+use crate::persistence::ConfigFormat;
+use crate::profiles::HotkeyProfiles;
+
+impl TypeUuid for ConfigDocument {
+    const TYPE_UUID: Uuid = Uuid::from_u128(0x6f0b_93a4_59c1_4d2e_8a7f_1c3e_5b2d_9e40);
+}
+
+/// Loads files ending in `.hotkeys.json`, `.hotkeys.ron` or `.hotkeys.toml`,
+/// depending on the enabled formats, as `ConfigDocument`s. The `HotkeyPlugin`
+/// of each action type migrates and converts them to its `HotkeyConfig`, so
+/// that several action types can be loaded as assets.
+pub struct HotkeyConfigLoader {
+    extensions: Vec<&'static str>,
+}
+
+impl Default for HotkeyConfigLoader {
+    fn default() -> Self {
+        Self {
+            extensions: ConfigFormat::ALL.iter().map(asset_extension).collect(),
+        }
+    }
+}
+
+impl AssetLoader for HotkeyConfigLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let format = load_context
+                .path()
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .and_then(ConfigFormat::from_extension)
+                .ok_or_else(|| anyhow::anyhow!("unknown hotkey config format"))?;
+            let document: ConfigDocument = parse_str(std::str::from_utf8(bytes)?, format)?;
+            load_context.set_default_asset(LoadedAsset::new(document));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &self.extensions
+    }
+}
+
+// Distinct from plain "json" etc., so that other assets of the app are
+// not loaded as hotkeys.
+fn asset_extension(format: &ConfigFormat) -> &'static str {
+    match *format {
+        #[cfg(feature = "json")]
+        ConfigFormat::Json => "hotkeys.json",
+        #[cfg(feature = "ron")]
+        ConfigFormat::Ron => "hotkeys.ron",
+        #[cfg(feature = "toml")]
+        ConfigFormat::Toml => "hotkeys.toml",
+    }
+}
+
+/// The handle to the hotkey config asset, along with the built-in
+/// config that fills in any actions the asset does not define.
+pub struct HotkeyConfigAsset<T: Sync + Send + 'static + Eq + Hash + Clone> {
+    pub handle: Handle<ConfigDocument>,
+    defaults: HotkeyConfig<T>,
+    migrations: HotkeyMigrations,
+    // The defaults currently in use, to tell which hotkeys the player changed.
+    applied: HotkeyConfig<T>,
+}
+
+pub(crate) fn register_config_asset<T>(
+    app: &mut App,
+    path: &str,
+    defaults: &HotkeyConfig<T>,
+    migrations: &HotkeyMigrations,
+) where
+    T: Sync + Send + 'static + Eq + Hash + Clone + DeserializeOwned,
+{
+    let handle = match app.world.get_resource::<AssetServer>() {
+        Some(asset_server) => asset_server.load(path),
+        None => {
+            error!(
+                "HotkeyPlugin::load_as_asset requires the AssetPlugin to be added first, \
+                 using the built-in hotkeys instead of {}",
+                path
+            );
+            return;
+        }
+    };
+    // The asset type is shared by the plugins of all action types.
+    if app.world.get_resource::<Assets<ConfigDocument>>().is_none() {
+        app.add_asset::<ConfigDocument>()
+            .init_asset_loader::<HotkeyConfigLoader>();
+    }
+    app.insert_resource(HotkeyConfigAsset {
+        handle,
+        defaults: defaults.clone(),
+        migrations: migrations.clone(),
+        applied: defaults.clone(),
+    })
+    .add_system(reload_config_system::<T>);
+}
+
+fn reload_config_system<T: Sync + Send + 'static + Eq + Hash + Clone + DeserializeOwned>(
+    mut events: EventReader<AssetEvent<ConfigDocument>>,
+    assets: Res<Assets<ConfigDocument>>,
+    mut config_asset: ResMut<HotkeyConfigAsset<T>>,
+    profiles: Option<ResMut<HotkeyProfiles<T>>>,
+    mut hotkey_states: ResMut<HotkeyStates<T>>,
+    listener_config: Option<ResMut<HotkeyConfig<T>>>,
+) {
+    let changed = events.iter().any(|event| match event {
+        AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+            *handle == config_asset.handle
+        }
+        AssetEvent::Removed { .. } => false,
+    });
+    if !changed {
+        return;
+    }
+    let mut document = match assets.get(&config_asset.handle) {
+        Some(document) => document.clone(),
+        None => return,
+    };
+    config_asset.migrations.migrate(&mut document);
+    let mut asset: HotkeyConfig<T> = document.into_config();
+    asset.update_from(&config_asset.defaults);
+    // Hotkeys changed by the player, e.g. saved ones, take precedence over the asset.
+    let mut config = hotkey_states.config.clone();
+    config.update_defaults(&config_asset.applied, &asset);
+    if let Some(mut profiles) = profiles {
+        profiles.update_active(config.clone());
+    }
+    // Keep the changes of the listener which are not applied yet.
+    if let Some(mut listener_config) = listener_config {
+        listener_config.update_defaults(&config_asset.applied, &asset);
+    }
+    hotkey_states.set_config(config);
+    config_asset.applied = asset;
+}
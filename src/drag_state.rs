@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use bevy::input::Input;
+use bevy::math::Vec2;
+use bevy::prelude::MouseButton;
+
+/// Tracks how far the mouse moved while each button was held.
+#[derive(Default)]
+pub(crate) struct DragState {
+    distances: HashMap<MouseButton, f32>,
+    offsets: HashMap<MouseButton, Vec2>,
+    dragging: Vec<MouseButton>,
+    started: Vec<MouseButton>,
+}
+
+impl DragState {
+    pub fn update(&mut self, mouse_input: &Input<MouseButton>, motion: Vec2, threshold: f32) {
+        self.distances
+            .retain(|button, _| mouse_input.pressed(*button));
+        self.offsets
+            .retain(|button, _| mouse_input.pressed(*button));
+        self.dragging.clear();
+        self.started.clear();
+        for button in mouse_input.get_pressed() {
+            let distance = self.distances.entry(*button).or_insert(0.0);
+            let was_dragging = *distance > threshold;
+            *distance += motion.length();
+            *self.offsets.entry(*button).or_default() += motion;
+            if *distance > threshold {
+                self.dragging.push(*button);
+                if !was_dragging {
+                    self.started.push(*button);
+                }
+            }
+        }
+    }
+
+    /// Buttons held while the mouse moved further than the threshold.
+    pub fn dragging(&self) -> &[MouseButton] {
+        &self.dragging
+    }
+
+    /// The total motion since the button was pressed.
+    pub fn offset(&self, button: MouseButton) -> Option<Vec2> {
+        self.offsets.get(&button).copied()
+    }
+
+    /// Buttons which started dragging this frame.
+    pub fn started(&self) -> &[MouseButton] {
+        &self.started
+    }
+}
@@ -0,0 +1,13 @@
+/// When input reaches the `HotkeyStates`, inserted as a resource by the
+/// `HotkeyPlugin`. Input that arrives while it is blocked is dropped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FocusPolicy {
+    /// Only while a window of the app is focused. Without a window, e.g. on headless
+    /// servers or before the window is created, input is always allowed.
+    #[default]
+    RequireFocus,
+    /// Only while a window is focused, so no input is allowed without one.
+    RequireWindow,
+    /// Always, regardless of focus.
+    Ignore,
+}
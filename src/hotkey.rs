@@ -1,80 +1,322 @@
 use std::fmt::Display;
 use std::iter;
+use std::str::FromStr;
 
-use bevy::input::mouse::MouseWheel;
-use bevy::input::Input;
-use bevy::prelude::KeyCode;
-use bevy::prelude::MouseButton;
+use bevy::math::Vec2;
+use serde::de::MapAccess;
+use serde::de::Visitor;
 use serde::Deserialize;
+use serde::Deserializer;
 use serde::Serialize;
+use serde::Serializer;
 
 use super::action::Action;
-use super::mouse_wheel_action::MouseWheelAction;
+use super::action::ParseHotkeyError;
 use crate::config;
+use crate::hotkey_input::HotkeyInput;
 use crate::modifier::Modifier;
+use crate::modifier::ModifierMatch;
 use crate::modifier::AVAILABLE_MODIFIERS;
+use crate::modifier::GENERIC_MODIFIERS;
+use crate::touch_action::TouchAction;
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Debug)]
 pub struct Hotkey {
     pub(crate) key: Action,
     pub(crate) modifiers: Vec<Modifier>,
 }
 
 impl Hotkey {
-    pub(crate) fn pressed(
-        &self,
-        keyboard_input: &Input<KeyCode>,
-        mouse_input: &Input<MouseButton>,
-    ) -> bool {
-        self.modifiers_pressed(keyboard_input)
-            && match self.key {
-                Action::Button(button) => mouse_input.pressed(button),
-                Action::Key(key) => keyboard_input.pressed(key),
+    pub(crate) fn pressed(&self, input: &HotkeyInput, modifier_match: ModifierMatch) -> bool {
+        self.modifiers_pressed(input, modifier_match)
+            && match &self.key {
+                Action::Button(button) => input.mouse.pressed(*button),
+                Action::Key(key) => input.keyboard.pressed(*key),
                 Action::Scroll(_) => false,
+                Action::Motion(action) => action.delta(input.motion) > input.motion_threshold,
+                Action::Drag(button) => input.drags.dragging().contains(button),
+                Action::ScanCode(scan_code) => input.scan_codes.pressed(*scan_code),
+                Action::Touch(TouchAction::Tap) => input.touches.iter().next().is_some(),
+                Action::Touch(TouchAction::Region(region)) => input
+                    .touches
+                    .iter()
+                    .any(|touch| input.touch_in_region(touch, region)),
+                Action::Touch(TouchAction::Fingers(fingers)) => {
+                    input.touches.iter().count() == *fingers as usize
+                }
+                Action::Char(c) => input.characters.contains(c),
             }
     }
 
-    pub(crate) fn just_pressed(
+    pub(crate) fn just_pressed(&self, input: &HotkeyInput, modifier_match: ModifierMatch) -> bool {
+        self.modifiers_pressed(input, modifier_match)
+            && match &self.key {
+                Action::Button(button) => input.mouse.just_pressed(*button),
+                Action::Key(key) => input.keyboard.just_pressed(*key),
+                Action::Scroll(action) => action.delta(input.scroll_steps) >= 1.0,
+                // Mouse motion has no press event, see `motion_pressed`.
+                Action::Motion(_) => false,
+                Action::Drag(button) => input.drags.started().contains(button),
+                Action::ScanCode(scan_code) => input.scan_codes.just_pressed(*scan_code),
+                Action::Touch(TouchAction::Tap) => {
+                    input.touches.iter_just_pressed().next().is_some()
+                }
+                Action::Touch(TouchAction::Region(region)) => input
+                    .touches
+                    .iter_just_pressed()
+                    .any(|touch| input.touch_in_region(touch, region)),
+                // The finger completing the count touched down this frame.
+                Action::Touch(TouchAction::Fingers(fingers)) => {
+                    input.touches.iter_just_pressed().next().is_some()
+                        && input.touches.iter().count() == *fingers as usize
+                }
+                Action::Char(c) => input.characters.contains(c),
+            }
+    }
+
+    pub(crate) fn is_motion(&self) -> bool {
+        matches!(self.key, Action::Motion(_) | Action::Drag(_))
+    }
+
+    /// Whether this is a pressed `Motion` binding. It is just pressed on the
+    /// first frame of the motion, which `HotkeyState` tells from the last frame.
+    pub(crate) fn motion_pressed(
         &self,
-        keyboard_input: &Input<KeyCode>,
-        mouse_input: &Input<MouseButton>,
-        mouse_wheel_events: &[&MouseWheel],
+        input: &HotkeyInput,
+        modifier_match: ModifierMatch,
     ) -> bool {
-        self.modifiers_pressed(keyboard_input)
-            && match &self.key {
-                Action::Button(button) => mouse_input.just_pressed(*button),
-                Action::Key(key) => keyboard_input.just_pressed(*key),
-                Action::Scroll(action) => mouse_wheel_events.iter().any(|event| match action {
-                    MouseWheelAction::Up => event.y > 0.0,
-                    MouseWheelAction::Down => event.y < 0.0,
-                }),
+        matches!(self.key, Action::Motion(_)) && self.pressed(input, modifier_match)
+    }
+
+    /// The mouse movement since the button of a pressed `Drag` binding was pressed.
+    pub(crate) fn drag_delta(
+        &self,
+        input: &HotkeyInput,
+        modifier_match: ModifierMatch,
+    ) -> Option<Vec2> {
+        match &self.key {
+            Action::Drag(button) if self.pressed(input, modifier_match) => {
+                input.drags.offset(*button)
             }
+            _ => None,
+        }
     }
 
-    fn modifiers_pressed(&self, input: &Input<KeyCode>) -> bool {
-        AVAILABLE_MODIFIERS.iter().all(|modifier| {
-            let modifier_pressed = modifier
-                .get_key_codes()
+    /// The total distance scrolled in the direction of this hotkey this frame.
+    pub(crate) fn scroll_delta(&self, input: &HotkeyInput, modifier_match: ModifierMatch) -> f32 {
+        match &self.key {
+            Action::Scroll(action) if self.modifiers_pressed(input, modifier_match) => input
+                .scroll_deltas
                 .iter()
-                .any(|key_code| input.pressed(*key_code));
-            self.modifiers.contains(modifier) == modifier_pressed
-        })
+                .map(|delta| action.delta(*delta))
+                .sum(),
+            _ => 0.0,
+        }
+    }
+
+    pub(crate) fn custom_modifiers(&self) -> impl Iterator<Item = &Modifier> {
+        self.modifiers
+            .iter()
+            .filter(|modifier| modifier.is_custom())
+    }
+
+    // Side-specific modifiers require exactly that side to be held,
+    // generic ones are satisfied by either side.
+    fn modifiers_pressed(&self, input: &HotkeyInput, modifier_match: ModifierMatch) -> bool {
+        // Typing a character may need shift, which must not block its binding.
+        let modifier_match = match (&self.key, modifier_match) {
+            (Action::Char(_), ModifierMatch::Exact) => ModifierMatch::Subset,
+            _ => modifier_match,
+        };
+        let pressed = |modifier: &Modifier| modifier.pressed(input.keyboard, input.mouse);
+        let builtin_pressed = GENERIC_MODIFIERS.iter().all(|modifier| {
+            let (left, right) = modifier.sides().unwrap();
+            let wants_left = self.modifiers.contains(&left);
+            let wants_right = self.modifiers.contains(&right);
+            let wants_any = self.modifiers.contains(modifier);
+            match modifier_match {
+                ModifierMatch::Ignore => true,
+                ModifierMatch::Subset => {
+                    (!wants_left || pressed(&left))
+                        && (!wants_right || pressed(&right))
+                        && (!wants_any || pressed(modifier))
+                }
+                ModifierMatch::Exact if wants_left || wants_right => {
+                    wants_left == pressed(&left) && wants_right == pressed(&right)
+                }
+                ModifierMatch::Exact => wants_any == pressed(modifier),
+            }
+        });
+        let custom_pressed = match modifier_match {
+            ModifierMatch::Ignore => true,
+            ModifierMatch::Subset => self.custom_modifiers().all(pressed),
+            // Custom modifiers of other bindings of the same key must not be held,
+            // e.g. Space for "H" next to "Space+H".
+            ModifierMatch::Exact => {
+                self.custom_modifiers().all(pressed)
+                    && input
+                        .custom_modifiers
+                        .iter()
+                        .filter(|(key, modifier)| {
+                            *key == self.key && !self.modifiers.contains(modifier)
+                        })
+                        .all(|(_, modifier)| !pressed(modifier))
+            }
+        };
+        builtin_pressed && custom_pressed
+    }
+}
+
+// The order of the modifiers doesn't matter.
+impl PartialEq for Hotkey {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+            && self
+                .modifiers
+                .iter()
+                .all(|modifier| other.modifiers.contains(modifier))
+            && other
+                .modifiers
+                .iter()
+                .all(|modifier| self.modifiers.contains(modifier))
     }
 }
 
 impl Display for Hotkey {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let key_name = match self.key {
-            Action::Key(key) => format!("{:?}", key),
-            _ => "".into(),
-        };
+        // Built-in modifiers in a fixed order, custom ones as they were bound.
         let content = AVAILABLE_MODIFIERS
             .iter()
             .filter(|modifier| self.modifiers.contains(modifier))
-            .map(|modifier| modifier.to_str())
-            .chain(iter::once(key_name.as_str()))
-            .collect::<Vec<&str>>()
+            .chain(self.custom_modifiers())
+            .map(Modifier::to_string)
+            .chain(iter::once(self.key.to_string()))
+            .collect::<Vec<String>>()
             .join(config::HOTKEY_SEPARATOR);
         write!(f, "{}", content)
     }
 }
+
+impl FromStr for Hotkey {
+    type Err = ParseHotkeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split(config::HOTKEY_SEPARATOR).map(str::trim).collect();
+        let key = parts.pop().ok_or_else(|| ParseHotkeyError(s.into()))?;
+        let modifiers = parts
+            .into_iter()
+            .map(Modifier::from_str)
+            .collect::<Result<_, _>>()?;
+        Ok(Hotkey {
+            key: key.parse()?,
+            modifiers,
+        })
+    }
+}
+
+impl Serialize for Hotkey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Hotkey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(HotkeyVisitor)
+    }
+}
+
+struct HotkeyVisitor;
+
+impl<'de> Visitor<'de> for HotkeyVisitor {
+    type Value = Hotkey;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a hotkey such as \"ctrl+S\"")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Hotkey, E> {
+        value.parse().map_err(E::custom)
+    }
+
+    // Hotkeys used to be stored as a struct of key and modifiers.
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Hotkey, A::Error> {
+        let mut key = None;
+        let mut modifiers = None;
+        while let Some(field) = map.next_key::<String>()? {
+            match field.as_str() {
+                "key" => key = Some(map.next_value()?),
+                "modifiers" => modifiers = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<serde::de::IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(Hotkey {
+            key: key.ok_or_else(|| serde::de::Error::missing_field("key"))?,
+            modifiers: modifiers.unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::KeyCode;
+
+    use super::*;
+
+    fn parse(s: &str) -> Hotkey {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_modifiers_and_key() {
+        assert_eq!(
+            parse("ctrl+shift+S"),
+            Hotkey {
+                key: Action::Key(KeyCode::S),
+                modifiers: vec![Modifier::Control, Modifier::Shift],
+            }
+        );
+        assert_eq!(parse("meta+A"), parse("super+A"));
+        assert_eq!(parse("altgr+A"), parse("ralt+A"));
+        assert_eq!(parse(" Ctrl + s "), parse("ctrl+S"));
+    }
+
+    #[test]
+    fn modifier_order_does_not_matter() {
+        assert_eq!(parse("shift+ctrl+S"), parse("ctrl+shift+S"));
+        assert_ne!(parse("ctrl+S"), parse("ctrl+shift+S"));
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for s in [
+            "S",
+            "ctrl+shift+S",
+            "ralt+Char#43",
+            "Space+H",
+            "MouseRight+ScrollUp",
+            "super+Scan17",
+            "TouchJump",
+        ] {
+            assert_eq!(parse(s).to_string(), s);
+        }
+        assert_eq!(parse("shift+ctrl+S").to_string(), "ctrl+shift+S");
+    }
+
+    #[test]
+    fn invalid_hotkeys() {
+        assert!("ctrl+".parse::<Hotkey>().is_err());
+        assert!("ScrollUp+S".parse::<Hotkey>().is_err());
+        assert!("foo+S".parse::<Hotkey>().is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn deserializes_the_old_struct_format() {
+        let hotkey: Hotkey =
+            serde_json::from_str(r#"{"key": {"Key": "S"}, "modifiers": ["Control"]}"#).unwrap();
+        assert_eq!(hotkey, parse("ctrl+S"));
+    }
+}
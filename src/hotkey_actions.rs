@@ -0,0 +1,96 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use bevy::log::warn;
+
+use crate::action::ParseHotkeyError;
+use crate::hotkey_config::HotkeyConfig;
+use crate::validation::ValidationRules;
+
+#[cfg(feature = "derive")]
+pub use bevy_hotkey_config_derive::HotkeyActions;
+
+/// Lists every action of the action type, so that configs can be checked
+/// for missing actions and settings menus can show all of them.
+/// Can be derived for enums, see `#[derive(HotkeyActions)]`.
+pub trait HotkeyActions: Sized + Eq + Hash + Clone {
+    fn all() -> Vec<Self>;
+
+    /// Hotkeys such as `"ctrl+S"` used by `HotkeyConfig::from_defaults`.
+    fn default_hotkeys(&self) -> Vec<&'static str> {
+        vec![]
+    }
+
+    fn display_name(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn description(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn category(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+impl<T: HotkeyActions> HotkeyConfig<T> {
+    /// Builds the config from `HotkeyActions::default_hotkeys`.
+    /// Fails on the first default hotkey which can't be parsed.
+    pub fn from_defaults() -> Result<Self, ParseHotkeyError> {
+        let mut config = Self::empty();
+        for name in T::all() {
+            for hotkey in name.default_hotkeys() {
+                config.insert_parsed(name.clone(), hotkey)?;
+            }
+        }
+        Ok(config)
+    }
+
+    /// Actions without any hotkeys, including those missing from the config.
+    pub fn unbound_actions(&self) -> Vec<T> {
+        T::all()
+            .into_iter()
+            .filter(|name| {
+                self.get(name)
+                    .map(|hotkeys| hotkeys.iter().next().is_none())
+                    .unwrap_or(true)
+            })
+            .collect()
+    }
+
+    /// Actions in the config which are not listed in `HotkeyActions::all`.
+    pub fn stale_actions(&self) -> Vec<T> {
+        let all = T::all();
+        self.iter()
+            .map(|(name, _)| name)
+            .filter(|name| !all.contains(name))
+            .cloned()
+            .collect()
+    }
+
+    pub fn remove_stale_actions(&mut self) {
+        for name in self.stale_actions() {
+            self.map.remove(&name);
+            self.repeat.remove(&name);
+            self.modifier_match.remove(&name);
+        }
+    }
+}
+
+impl<T: HotkeyActions> ValidationRules<T> {
+    pub fn require_all(self) -> Self {
+        T::all()
+            .into_iter()
+            .fold(self, |rules, name| rules.require(name))
+    }
+}
+
+pub(crate) fn warn_unbound_actions<T: HotkeyActions + Debug>(config: &HotkeyConfig<T>) {
+    for name in config.unbound_actions() {
+        warn!("No hotkeys bound to {:?}", name);
+    }
+    for name in config.stale_actions() {
+        warn!("Hotkeys bound to unknown action {:?}", name);
+    }
+}
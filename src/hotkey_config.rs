@@ -1,43 +1,265 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::hash::Hash;
 
+use bevy::input::mouse::MouseScrollUnit;
+use bevy::input::mouse::MouseWheel;
+use bevy::math::Vec2;
 use bevy::prelude::KeyCode;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
+use serde::Deserializer;
 use serde::Serialize;
+use serde::Serializer;
 
 use super::Hotkeys;
 use crate::action::Action;
+use crate::action::ParseHotkeyError;
 use crate::config;
 use crate::hotkey::Hotkey;
+use crate::migration::ConfigDocument;
+use crate::migration::HotkeyMigrations;
 use crate::modifier::Modifier;
+use crate::modifier::ModifierMatch;
+use crate::persistence::parse_str;
+use crate::persistence::to_string;
+use crate::persistence::ConfigFormat;
+use crate::persistence::PersistenceError;
+use crate::serialization::action_to_string;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug)]
 pub struct HotkeyConfig<T: Hash + Eq + Clone> {
     pub(crate) map: HashMap<T, Hotkeys>,
+    pub(crate) repeat: HashMap<T, ActionRepeat>,
+    pub(crate) modifier_match: HashMap<T, ModifierMatch>,
+    pub(crate) version: u32,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct KeyRepeatSettings {
     pub initial_delay: f32,
     pub delay: f32,
+    #[serde(default)]
+    pub curve: RepeatCurve,
+    /// Limits the repeats fired within one frame, e.g. after a frame hitch.
+    /// The remaining repeats fire in the following frames.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_repeats_per_frame: Option<u32>,
 }
 
 impl Default for KeyRepeatSettings {
     fn default() -> Self {
-        KeyRepeatSettings {
-            initial_delay: config::DEFAULT_REPEAT_INITIAL_DELAY,
-            delay: config::DEFAULT_REPEAT_DELAY,
+        KeyRepeatSettings::new(
+            config::DEFAULT_REPEAT_INITIAL_DELAY,
+            config::DEFAULT_REPEAT_DELAY,
+        )
+    }
+}
+
+impl KeyRepeatSettings {
+    pub fn new(initial_delay: f32, delay: f32) -> Self {
+        Self {
+            initial_delay,
+            delay,
+            curve: RepeatCurve::Constant,
+            max_repeats_per_frame: None,
+        }
+    }
+
+    pub fn with_curve(mut self, curve: RepeatCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    pub fn with_max_repeats_per_frame(mut self, max_repeats: u32) -> Self {
+        self.max_repeats_per_frame = Some(max_repeats);
+        self
+    }
+
+    /// The time to wait for the next repeat after `repeats` repeats
+    /// have already fired since the key was pressed.
+    pub fn delay_after(&self, repeats: u32) -> f32 {
+        let delay = if repeats == 0 {
+            self.initial_delay
+        } else {
+            match &self.curve {
+                RepeatCurve::Constant => self.delay,
+                RepeatCurve::Stepped { steps } => steps
+                    .iter()
+                    .filter(|step| step.after <= repeats)
+                    .max_by_key(|step| step.after)
+                    .map(|step| step.delay)
+                    .unwrap_or(self.delay),
+                RepeatCurve::Exponential { factor, min_delay } => {
+                    (self.delay * factor.powi(repeats as i32 - 1)).max(*min_delay)
+                }
+            }
+        };
+        delay.max(config::MIN_REPEAT_DELAY)
+    }
+}
+
+/// How the delay between repeats changes while a key is held.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum RepeatCurve {
+    /// Always wait `delay`.
+    #[default]
+    Constant,
+    /// Wait `delay` until the first step is reached, then the delay of the
+    /// latest step reached.
+    Stepped { steps: Vec<RepeatStep> },
+    /// Multiply the delay by `factor` after every repeat, down to `min_delay`.
+    Exponential { factor: f32, min_delay: f32 },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RepeatStep {
+    /// The number of repeats after which this delay is used.
+    pub after: u32,
+    pub delay: f32,
+}
+
+/// How scrolling with mouse wheels and trackpads is measured.
+#[derive(Clone, Debug)]
+pub struct ScrollSettings {
+    /// The unit of `HotkeyStates::scroll_delta`. Scroll events in the other
+    /// unit are converted.
+    pub unit: MouseScrollUnit,
+    pub pixels_per_line: f32,
+    /// How many lines have to be scrolled in one direction before a scroll
+    /// binding is `just_pressed`, so that small pixel deltas from trackpads
+    /// don't trigger it on every event. Must be positive.
+    pub threshold: f32,
+}
+
+impl Default for ScrollSettings {
+    fn default() -> Self {
+        Self {
+            unit: MouseScrollUnit::Line,
+            pixels_per_line: config::DEFAULT_PIXELS_PER_LINE,
+            threshold: config::DEFAULT_SCROLL_THRESHOLD,
+        }
+    }
+}
+
+impl ScrollSettings {
+    pub(crate) fn delta(&self, event: &MouseWheel) -> Vec2 {
+        let delta = Vec2::new(event.x, event.y);
+        match (event.unit, self.unit) {
+            (MouseScrollUnit::Line, MouseScrollUnit::Pixel) => delta * self.pixels_per_line,
+            (MouseScrollUnit::Pixel, MouseScrollUnit::Line) => delta / self.pixels_per_line,
+            _ => delta,
+        }
+    }
+
+    pub(crate) fn lines(&self, event: &MouseWheel) -> Vec2 {
+        let delta = Vec2::new(event.x, event.y);
+        match event.unit {
+            MouseScrollUnit::Line => delta,
+            MouseScrollUnit::Pixel => delta / self.pixels_per_line,
+        }
+    }
+
+    /// Adds the lines scrolled this frame to `distance` and takes out the
+    /// whole steps of `threshold` lines, which are returned.
+    pub(crate) fn steps(&self, distance: &mut Vec2, lines: Vec2) -> Vec2 {
+        // Scrolling back discards what was scrolled the other way.
+        if distance.x * lines.x < 0.0 {
+            distance.x = 0.0;
+        }
+        if distance.y * lines.y < 0.0 {
+            distance.y = 0.0;
         }
+        *distance += lines;
+        let steps = (*distance / self.threshold).abs().floor() * distance.signum();
+        *distance -= steps * self.threshold;
+        steps
     }
 }
 
+/// When mouse movement triggers `Motion` and `Drag` bindings, in pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MotionSettings {
+    /// The distance the mouse has to move in one direction within a frame.
+    pub threshold: f32,
+    /// The distance the mouse has to move while a button is held before
+    /// it counts as dragging, so that clicks don't start drags.
+    pub drag_threshold: f32,
+}
+
+impl Default for MotionSettings {
+    fn default() -> Self {
+        Self {
+            threshold: config::DEFAULT_MOTION_THRESHOLD,
+            drag_threshold: config::DEFAULT_DRAG_THRESHOLD,
+        }
+    }
+}
+
+/// A rectangle in normalized window coordinates, from (0, 0) to (1, 1)
+/// with the origin where bevy reports touch positions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TouchRegion {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl TouchRegion {
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        Self { min, max }
+    }
+
+    pub fn contains(&self, position: Vec2) -> bool {
+        position.cmpge(self.min).all() && position.cmple(self.max).all()
+    }
+}
+
+/// The screen regions `Touch` bindings refer to by name, e.g. `TouchJump`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TouchSettings {
+    pub regions: HashMap<String, TouchRegion>,
+}
+
+impl TouchSettings {
+    /// Panics if the name is empty or contains whitespace or the hotkey
+    /// separator, since `Touch{name}` could not be parsed back.
+    pub fn with_region(mut self, name: impl Into<String>, region: TouchRegion) -> Self {
+        let name = name.into();
+        assert!(
+            !name.is_empty()
+                && !name.contains(char::is_whitespace)
+                && !name.contains(config::HOTKEY_SEPARATOR),
+            "invalid touch region name {:?}",
+            name
+        );
+        self.regions.insert(name, region);
+        self
+    }
+}
+
+/// Overrides the global `KeyRepeatSettings` for a single action.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode")]
+pub enum ActionRepeat {
+    Never,
+    Custom(KeyRepeatSettings),
+}
+
 impl<T: Eq + Hash + Clone> HotkeyConfig<T> {
     pub fn empty() -> Self {
         Self {
             map: HashMap::new(),
+            repeat: HashMap::new(),
+            modifier_match: HashMap::new(),
+            version: 0,
         }
     }
 
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
     pub fn insert_normal(&mut self, name: T, key_code: KeyCode) {
         let hotkey = Hotkey {
             key: Action::Key(key_code),
@@ -49,22 +271,104 @@ impl<T: Eq + Hash + Clone> HotkeyConfig<T> {
     pub fn insert_with_modifiers(&mut self, name: T, key_code: KeyCode, modifiers: &[Modifier]) {
         let hotkey = Hotkey {
             key: Action::Key(key_code),
-            modifiers: modifiers.into_iter().cloned().collect(),
+            modifiers: modifiers.to_vec(),
         };
         self.insert(name, hotkey)
     }
 
+    /// Add a hotkey written as in config files, such as `"ctrl+S"`.
+    pub fn insert_parsed(&mut self, name: T, hotkey: &str) -> Result<(), ParseHotkeyError> {
+        self.insert(name, hotkey.parse()?);
+        Ok(())
+    }
+
     pub fn update_from(&mut self, config: &HotkeyConfig<T>) {
         for (key, value) in config.map.iter() {
             self.map.entry(key.clone()).or_insert(value.clone());
         }
+        for (key, value) in config.repeat.iter() {
+            self.repeat.entry(key.clone()).or_insert(value.clone());
+        }
+        for (key, value) in config.modifier_match.iter() {
+            self.modifier_match.entry(key.clone()).or_insert(*value);
+        }
     }
 
     pub fn get(&self, name: &T) -> Option<&Hotkeys> {
         self.map.get(name)
     }
 
-    pub(crate) fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a T, &'a Hotkeys)> {
+    pub fn set_key_repeat(&mut self, name: T, key_repeat: KeyRepeatSettings) {
+        self.repeat.insert(name, ActionRepeat::Custom(key_repeat));
+    }
+
+    pub fn disable_key_repeat(&mut self, name: T) {
+        self.repeat.insert(name, ActionRepeat::Never);
+    }
+
+    /// Go back to the global repeat settings for this action.
+    pub fn reset_key_repeat(&mut self, name: &T) {
+        self.repeat.remove(name);
+    }
+
+    /// `None` if the action uses the global repeat settings.
+    pub fn key_repeat(&self, name: &T) -> Option<&ActionRepeat> {
+        self.repeat.get(name)
+    }
+
+    pub fn set_modifier_match(&mut self, name: T, modifier_match: ModifierMatch) {
+        self.modifier_match.insert(name, modifier_match);
+    }
+
+    /// `ModifierMatch::Exact` unless set otherwise.
+    pub fn modifier_match(&self, name: &T) -> ModifierMatch {
+        self.modifier_match.get(name).copied().unwrap_or_default()
+    }
+
+    /// Take over the settings of `new_defaults` for every action whose
+    /// settings still match `old_defaults`, keeping those the player changed.
+    pub(crate) fn update_defaults(
+        &mut self,
+        old_defaults: &HotkeyConfig<T>,
+        new_defaults: &HotkeyConfig<T>,
+    ) {
+        update_unchanged(&mut self.map, &old_defaults.map, &new_defaults.map);
+        update_unchanged(&mut self.repeat, &old_defaults.repeat, &new_defaults.repeat);
+        update_unchanged(
+            &mut self.modifier_match,
+            &old_defaults.modifier_match,
+            &new_defaults.modifier_match,
+        );
+    }
+
+    /// The custom modifiers of all bindings together with the key they modify.
+    pub(crate) fn custom_modifiers(&self) -> Vec<(Action, Modifier)> {
+        let mut modifiers = vec![];
+        for hotkey in self.map.values().flat_map(Hotkeys::iter) {
+            for modifier in hotkey.custom_modifiers() {
+                let entry = (hotkey.key.clone(), modifier.clone());
+                if !modifiers.contains(&entry) {
+                    modifiers.push(entry);
+                }
+            }
+        }
+        modifiers
+    }
+
+    /// The repeat settings of the action, or `None` if it never repeats.
+    pub(crate) fn repeat_settings<'a>(
+        &'a self,
+        name: &T,
+        default: &'a KeyRepeatSettings,
+    ) -> Option<&'a KeyRepeatSettings> {
+        match self.repeat.get(name) {
+            None => Some(default),
+            Some(ActionRepeat::Never) => None,
+            Some(ActionRepeat::Custom(key_repeat)) => Some(key_repeat),
+        }
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&T, &Hotkeys)> {
         self.map.iter()
     }
 
@@ -78,10 +382,259 @@ impl<T: Eq + Hash + Clone> HotkeyConfig<T> {
     }
 }
 
+impl<T: Eq + Hash + Clone + DeserializeOwned> HotkeyConfig<T> {
+    pub fn load_from_str(contents: &str, format: ConfigFormat) -> Result<Self, PersistenceError> {
+        let document: ConfigDocument = parse_str(contents, format)?;
+        Ok(document.into_config())
+    }
+
+    /// Load the config, upgrading it to the newest version first.
+    pub fn load_from_str_with_migrations(
+        contents: &str,
+        format: ConfigFormat,
+        migrations: &HotkeyMigrations,
+    ) -> Result<Self, PersistenceError> {
+        let mut document: ConfigDocument = parse_str(contents, format)?;
+        migrations.migrate(&mut document);
+        Ok(document.into_config())
+    }
+}
+
+impl<T: Eq + Hash + Clone + Serialize> HotkeyConfig<T> {
+    pub fn save_to_string(&self, format: ConfigFormat) -> Result<String, PersistenceError> {
+        to_string(self, format)
+    }
+
+    /// Save the config as the newest version of the migrations, so that they
+    /// don't run again on it. Use this for config files shipped with the game.
+    pub fn save_to_string_with_migrations(
+        &self,
+        format: ConfigFormat,
+        migrations: &HotkeyMigrations,
+    ) -> Result<String, PersistenceError> {
+        to_string(&VersionedConfig(self, migrations.current_version()), format)
+    }
+}
+
+// The actions are stored as strings (sorted, so that saved files are stable),
+// since not every format supports arbitrary map keys.
+#[derive(Serialize)]
+struct SerializedConfig<'a> {
+    version: u32,
+    map: BTreeMap<String, &'a Hotkeys>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    repeat: BTreeMap<String, &'a ActionRepeat>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    modifier_match: BTreeMap<String, &'a ModifierMatch>,
+}
+
+// A config serialized with the given version instead of its own.
+pub(crate) struct VersionedConfig<'a, T: Eq + Hash + Clone>(pub &'a HotkeyConfig<T>, pub u32);
+
+impl<T: Eq + Hash + Clone + Serialize> Serialize for VersionedConfig<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let VersionedConfig(config, version) = self;
+        SerializedConfig {
+            version: *version,
+            map: string_keys(&config.map).map_err(serde::ser::Error::custom)?,
+            repeat: string_keys(&config.repeat).map_err(serde::ser::Error::custom)?,
+            modifier_match: string_keys(&config.modifier_match)
+                .map_err(serde::ser::Error::custom)?,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<T: Eq + Hash + Clone + Serialize> Serialize for HotkeyConfig<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        VersionedConfig(self, self.version).serialize(serializer)
+    }
+}
+
+fn string_keys<T: Serialize, V>(
+    map: &HashMap<T, V>,
+) -> Result<BTreeMap<String, &V>, serde::de::value::Error> {
+    map.iter()
+        .map(|(name, value)| action_to_string(name).map(|name| (name, value)))
+        .collect()
+}
+
+impl<'de, T: Eq + Hash + Clone + DeserializeOwned> Deserialize<'de> for HotkeyConfig<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        ConfigDocument::deserialize(deserializer).map(ConfigDocument::into_config)
+    }
+}
+
 impl<T: Eq + Hash + Clone> FromIterator<(T, Hotkeys)> for HotkeyConfig<T> {
     fn from_iter<I: IntoIterator<Item = (T, Hotkeys)>>(iter: I) -> Self {
         Self {
             map: iter.into_iter().collect(),
+            repeat: HashMap::new(),
+            modifier_match: HashMap::new(),
+            version: 0,
+        }
+    }
+}
+
+fn update_unchanged<T: Eq + Hash + Clone, V: Clone + PartialEq>(
+    settings: &mut HashMap<T, V>,
+    old: &HashMap<T, V>,
+    new: &HashMap<T, V>,
+) {
+    for (name, value) in old.iter() {
+        if settings.get(name) == Some(value) {
+            match new.get(name) {
+                Some(value) => settings.insert(name.clone(), value.clone()),
+                None => settings.remove(name),
+            };
+        }
+    }
+    for (name, value) in new.iter() {
+        if !old.contains_key(name) && !settings.contains_key(name) {
+            settings.insert(name.clone(), value.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    enum TestAction {
+        Jump,
+        Duck,
+        Fire,
+    }
+
+    fn config() -> HotkeyConfig<TestAction> {
+        let mut config = HotkeyConfig::empty();
+        config.insert_parsed(TestAction::Jump, "Space").unwrap();
+        config.insert_parsed(TestAction::Jump, "W").unwrap();
+        config.insert_parsed(TestAction::Duck, "ctrl+S").unwrap();
+        config.insert_parsed(TestAction::Fire, "Char#43").unwrap();
+        config.set_key_repeat(
+            TestAction::Jump,
+            KeyRepeatSettings::new(0.3, 0.1).with_curve(RepeatCurve::Exponential {
+                factor: 0.5,
+                min_delay: 0.02,
+            }),
+        );
+        config.disable_key_repeat(TestAction::Duck);
+        config.set_modifier_match(TestAction::Fire, ModifierMatch::Subset);
+        config
+    }
+
+    #[test]
+    fn formats_round_trip() {
+        let config = config();
+        for format in ConfigFormat::ALL {
+            let saved = config.save_to_string(*format).unwrap();
+            let loaded = HotkeyConfig::load_from_str(&saved, *format).unwrap();
+            assert_eq!(loaded.map, config.map, "{:?}", format);
+            assert_eq!(loaded.repeat, config.repeat, "{:?}", format);
+            assert_eq!(loaded.modifier_match, config.modifier_match, "{:?}", format);
         }
     }
+
+    #[test]
+    fn saving_with_migrations_stamps_the_version() {
+        let migrations = HotkeyMigrations::new()
+            .add_migration(|document| document.rename_action("Crouch", "Duck"))
+            .add_migration(|document| document.remove_action("Crouch"));
+        for format in ConfigFormat::ALL {
+            let saved = config()
+                .save_to_string_with_migrations(*format, &migrations)
+                .unwrap();
+            let loaded: HotkeyConfig<TestAction> =
+                HotkeyConfig::load_from_str(&saved, *format).unwrap();
+            assert_eq!(loaded.version(), 2, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn constant_and_stepped_delays() {
+        let constant = KeyRepeatSettings::new(0.5, 0.1);
+        assert_eq!(constant.delay_after(0), 0.5);
+        assert_eq!(constant.delay_after(7), 0.1);
+
+        let stepped = constant.with_curve(RepeatCurve::Stepped {
+            steps: vec![
+                RepeatStep {
+                    after: 3,
+                    delay: 0.05,
+                },
+                RepeatStep {
+                    after: 10,
+                    delay: 0.02,
+                },
+            ],
+        });
+        assert_eq!(stepped.delay_after(0), 0.5);
+        assert_eq!(stepped.delay_after(2), 0.1);
+        assert_eq!(stepped.delay_after(3), 0.05);
+        assert_eq!(stepped.delay_after(9), 0.05);
+        assert_eq!(stepped.delay_after(100), 0.02);
+    }
+
+    #[test]
+    fn exponential_delays() {
+        let settings = KeyRepeatSettings::new(0.5, 0.2).with_curve(RepeatCurve::Exponential {
+            factor: 0.5,
+            min_delay: 0.04,
+        });
+        assert_eq!(settings.delay_after(1), 0.2);
+        assert_eq!(settings.delay_after(2), 0.1);
+        assert_eq!(settings.delay_after(3), 0.05);
+        assert_eq!(settings.delay_after(4), 0.04);
+        assert_eq!(settings.delay_after(50), 0.04);
+    }
+
+    #[test]
+    fn delays_have_a_minimum() {
+        let settings = KeyRepeatSettings::new(0.0, 0.0);
+        assert_eq!(settings.delay_after(0), config::MIN_REPEAT_DELAY);
+        assert_eq!(settings.delay_after(1), config::MIN_REPEAT_DELAY);
+    }
+
+    #[test]
+    fn scroll_steps_keep_the_remainder() {
+        let settings = ScrollSettings {
+            threshold: 2.0,
+            ..Default::default()
+        };
+        let mut distance = Vec2::ZERO;
+        assert_eq!(
+            settings.steps(&mut distance, Vec2::new(0.0, 1.5)),
+            Vec2::ZERO
+        );
+        assert_eq!(
+            settings.steps(&mut distance, Vec2::new(0.0, 3.0)),
+            Vec2::new(0.0, 2.0)
+        );
+        assert_eq!(distance, Vec2::new(0.0, 0.5));
+        // Scrolling back discards the remainder.
+        assert_eq!(
+            settings.steps(&mut distance, Vec2::new(0.0, -2.0)),
+            Vec2::new(0.0, -1.0)
+        );
+        assert_eq!(distance, Vec2::ZERO);
+    }
+
+    #[test]
+    fn pixels_are_converted_to_lines() {
+        let settings = ScrollSettings::default();
+        let event = MouseWheel {
+            unit: MouseScrollUnit::Pixel,
+            x: 0.0,
+            y: settings.pixels_per_line * 3.0,
+        };
+        assert_eq!(settings.lines(&event), Vec2::new(0.0, 3.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid touch region name")]
+    fn touch_region_names_must_parse() {
+        TouchSettings::default().with_region("jump+run", TouchRegion::new(Vec2::ZERO, Vec2::ONE));
+    }
 }
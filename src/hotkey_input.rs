@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+
+use bevy::input::touch::Touch;
+use bevy::input::touch::Touches;
+use bevy::input::Input;
+use bevy::math::Vec2;
+use bevy::prelude::KeyCode;
+use bevy::prelude::MouseButton;
+
+use crate::action::Action;
+use crate::drag_state::DragState;
+use crate::hotkey_config::TouchRegion;
+use crate::modifier::Modifier;
+
+/// Everything hotkeys are checked against in a frame.
+pub(crate) struct HotkeyInput<'a> {
+    pub keyboard: &'a Input<KeyCode>,
+    pub mouse: &'a Input<MouseButton>,
+    pub scan_codes: &'a Input<u32>,
+    /// The scroll deltas of this frame in the unit of the `ScrollSettings`.
+    pub scroll_deltas: &'a [Vec2],
+    /// The whole `ScrollSettings::threshold` steps scrolled up to this frame.
+    pub scroll_steps: Vec2,
+    /// The sum of this frame's mouse motion events.
+    pub motion: Vec2,
+    pub motion_threshold: f32,
+    pub drags: &'a DragState,
+    pub touches: &'a Touches,
+    pub touch_regions: &'a HashMap<String, TouchRegion>,
+    /// `None` without a window, in which case no touch is in any region.
+    pub window_size: Option<Vec2>,
+    /// The characters typed this frame.
+    pub characters: &'a [char],
+    /// The custom modifiers used by the bindings of the config, with the key
+    /// they modify. While one of them is held, it blocks the bindings of the
+    /// same key which match modifiers exactly.
+    pub custom_modifiers: &'a [(Action, Modifier)],
+}
+
+impl HotkeyInput<'_> {
+    pub fn touch_in_region(&self, touch: &Touch, region: &str) -> bool {
+        match (self.touch_regions.get(region), self.window_size) {
+            (Some(region), Some(window_size)) => region.contains(touch.position() / window_size),
+            _ => false,
+        }
+    }
+}
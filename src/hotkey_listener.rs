@@ -1,17 +1,25 @@
 use core::hash::Hash;
 
+use bevy::input::mouse::MouseWheel;
 use bevy::input::Input;
+use bevy::log::error;
+use bevy::math::Vec2;
 use bevy::prelude::Component;
+use bevy::prelude::EventReader;
 use bevy::prelude::KeyCode;
+use bevy::prelude::MouseButton;
 use bevy::prelude::Res;
 use bevy::prelude::ResMut;
+use bevy::window::ReceivedCharacter;
 
 use crate::action::Action;
 use crate::hotkey::Hotkey;
 use crate::hotkey_config::HotkeyConfig;
 use crate::hotkey_states::HotkeyStates;
 use crate::modifier::Modifier;
-use crate::modifier::AVAILABLE_MODIFIERS;
+use crate::mouse_wheel_action::MouseWheelAction;
+use crate::persistence::HotkeyPersistence;
+use crate::profiles::HotkeyProfiles;
 
 #[derive(Component)]
 pub struct HotkeyListener<T> {
@@ -19,6 +27,17 @@ pub struct HotkeyListener<T> {
     cancel_action: Action,
     remove_action: Action,
     should_apply_settings: bool,
+    // Keys pressed since listening started. The last one becomes the key
+    // of the hotkey, the others its custom modifiers.
+    captured_keys: Vec<KeyCode>,
+    // The built-in modifiers and mouse buttons held when the last key was
+    // captured, so that releasing them first doesn't change the hotkey.
+    captured_modifiers: Vec<Modifier>,
+    // Mouse buttons held when listening started, such as the click on the
+    // settings button, which must not become modifiers. `None` until the
+    // listener has seen the input.
+    ignored_buttons: Option<Vec<MouseButton>>,
+    capture_characters: bool,
 }
 
 impl<T> HotkeyListener<T> {
@@ -28,6 +47,10 @@ impl<T> HotkeyListener<T> {
             cancel_action,
             remove_action,
             should_apply_settings: false,
+            captured_keys: vec![],
+            captured_modifiers: vec![],
+            ignored_buttons: None,
+            capture_characters: false,
         }
     }
 }
@@ -37,11 +60,22 @@ where
     T: Clone + Sync + Send + 'static + PartialEq + Eq + Hash,
 {
     pub fn set_currently_listening(&mut self, currently_listening: &T, num: usize) {
-        self.currently_listening = Some((currently_listening.clone(), num))
+        self.currently_listening = Some((currently_listening.clone(), num));
+        self.captured_keys.clear();
+        self.captured_modifiers.clear();
+        self.ignored_buttons = None;
     }
 
     pub fn clear_currently_listening(&mut self) {
-        self.currently_listening = None
+        self.currently_listening = None;
+        self.captured_keys.clear();
+        self.captured_modifiers.clear();
+    }
+
+    /// Bind the next typed character instead of the keys used to type it,
+    /// e.g. `?` regardless of where it is on the keyboard layout.
+    pub fn set_capture_characters(&mut self, capture_characters: bool) {
+        self.capture_characters = capture_characters;
     }
 
     pub fn apply_settings(&mut self) {
@@ -52,34 +86,103 @@ where
         mut listener: ResMut<Self>,
         mut settings_hotkeys: ResMut<HotkeyConfig<T>>,
         input: Res<Input<KeyCode>>,
+        mouse_input: Res<Input<MouseButton>>,
+        mut mouse_wheel_events: EventReader<MouseWheel>,
+        mut received_characters: EventReader<ReceivedCharacter>,
     ) {
+        // Read the events on every frame, so that scrolling or typing from
+        // before listening started is not taken for the new binding.
+        let scroll = mouse_wheel_events
+            .iter()
+            .find_map(|event| MouseWheelAction::from_delta(Vec2::new(event.x, event.y)));
+        // Control characters are left to the keys, so that the cancel key still works.
+        let character = received_characters
+            .iter()
+            .map(|event| event.char)
+            .find(|c| !c.is_control());
         if listener.currently_listening.is_none() {
             return;
         }
-        let (modifiers_pressed, other_keys_pressed): (Vec<KeyCode>, Vec<KeyCode>) = input
-            .get_pressed()
-            .partition(|key| AVAILABLE_MODIFIERS.iter().any(|modifier| modifier.is(key)));
-        let modifiers_pressed = modifiers_pressed
-            .into_iter()
-            .filter_map(|key_code| Modifier::from_key_code(&key_code))
+        let ignored_buttons = listener
+            .ignored_buttons
+            .get_or_insert_with(|| mouse_input.get_pressed().copied().collect());
+        // Buttons count again once they were released.
+        ignored_buttons.retain(|button| mouse_input.pressed(*button));
+        let ignored_buttons = ignored_buttons.clone();
+        if let Some(c) = character.filter(|_| listener.capture_characters) {
+            listener.captured_keys.clear();
+            listener.assign(&mut settings_hotkeys, vec![], Action::Char(c));
+            return;
+        }
+        // Built-in modifier keys are never the key of a hotkey.
+        let new_keys: Vec<KeyCode> = input
+            .get_just_pressed()
+            .filter(|key| Modifier::from_key_code(key).is_none())
+            .copied()
             .collect();
-        if other_keys_pressed.len() == 1 {
-            let pressed_key = other_keys_pressed.first().unwrap();
-            listener.assign(
-                &mut settings_hotkeys,
-                modifiers_pressed,
-                Action::Key(pressed_key.clone()),
-            );
+        if !new_keys.is_empty() {
+            for key in new_keys {
+                if !listener.captured_keys.contains(&key) {
+                    listener.captured_keys.push(key);
+                }
+            }
+            listener.captured_modifiers = held_modifiers(&input, &mouse_input, &ignored_buttons);
         }
+        // Keys are only assigned once all of them are released, since
+        // "Space" can't be told apart from the start of "Space+H" before.
+        let released = !listener.captured_keys.is_empty()
+            && listener
+                .captured_keys
+                .iter()
+                .all(|key| !input.pressed(*key));
+        let mut custom_keys = std::mem::take(&mut listener.captured_keys);
+        let (action, mut modifiers) = match scroll {
+            Some(scroll) => {
+                custom_keys.retain(|key| input.pressed(*key));
+                (
+                    Action::Scroll(scroll),
+                    held_modifiers(&input, &mouse_input, &ignored_buttons),
+                )
+            }
+            None if released => (
+                Action::Key(custom_keys.pop().unwrap()),
+                std::mem::take(&mut listener.captured_modifiers),
+            ),
+            None => {
+                listener.captured_keys = custom_keys;
+                return;
+            }
+        };
+        modifiers.extend(custom_keys.into_iter().map(Modifier::Key));
+        listener.assign(&mut settings_hotkeys, modifiers, action);
     }
 
     pub(crate) fn apply_hotkey_system(
-        listener: ResMut<Self>,
+        mut listener: ResMut<Self>,
         config: Res<HotkeyConfig<T>>,
         mut live_hotkeys: ResMut<HotkeyStates<T>>,
+        persistence: Option<Res<HotkeyPersistence<T>>>,
+        mut profiles: Option<ResMut<HotkeyProfiles<T>>>,
     ) {
         if listener.should_apply_settings {
-            live_hotkeys.config = config.clone();
+            listener.should_apply_settings = false;
+            live_hotkeys.set_config(config.clone());
+            if let Some(profiles) = &mut profiles {
+                profiles.update_active(config.clone());
+            }
+            if let Some(persistence) = persistence {
+                let result = match &profiles {
+                    Some(profiles) => persistence.save_profiles(profiles),
+                    None => persistence.save(&config),
+                };
+                if let Err(err) = result {
+                    error!(
+                        "Failed to save hotkeys to {}: {}",
+                        persistence.path().display(),
+                        err
+                    );
+                }
+            }
         }
     }
 
@@ -105,3 +208,24 @@ where
         }
     }
 }
+
+/// The held built-in modifiers, independent of the side, and mouse buttons.
+fn held_modifiers(
+    input: &Input<KeyCode>,
+    mouse_input: &Input<MouseButton>,
+    ignored_buttons: &[MouseButton],
+) -> Vec<Modifier> {
+    let mut modifiers = vec![];
+    for modifier in input.get_pressed().filter_map(Modifier::from_key_code) {
+        if !modifiers.contains(&modifier) {
+            modifiers.push(modifier);
+        }
+    }
+    modifiers.extend(
+        mouse_input
+            .get_pressed()
+            .filter(|button| !ignored_buttons.contains(button))
+            .map(|button| Modifier::Button(*button)),
+    );
+    modifiers
+}
@@ -1,18 +1,48 @@
+use std::fmt::Debug;
 use std::hash::Hash;
+use std::path::PathBuf;
 
+use bevy::app::Events;
+use bevy::ecs::system::Resource;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::mouse::MouseMotion;
+use bevy::input::mouse::MouseWheel;
+use bevy::input::touch::Touches;
+use bevy::input::Input;
+use bevy::log::error;
+use bevy::prelude::App;
+use bevy::prelude::CoreStage;
+use bevy::prelude::KeyCode;
+use bevy::prelude::MouseButton;
 use bevy::prelude::ParallelSystemDescriptorCoercion;
 use bevy::prelude::Plugin;
 use bevy::prelude::ResMut;
 use bevy::prelude::SystemLabel;
+use bevy::window::ReceivedCharacter;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
 use super::hotkey_states::reset_input_system;
 use super::hotkey_states::set_hotkey_states_from_input_system;
 use super::hotkey_states::HotkeyStates;
 use super::window_focus_state::WindowFocusState;
 use crate::action::Action;
+use crate::config_asset::register_config_asset;
+use crate::focus_policy::FocusPolicy;
+use crate::hotkey_actions::warn_unbound_actions;
+use crate::hotkey_actions::HotkeyActions;
 use crate::hotkey_config::HotkeyConfig;
 use crate::hotkey_config::KeyRepeatSettings;
+use crate::hotkey_config::MotionSettings;
+use crate::hotkey_config::ScrollSettings;
+use crate::hotkey_config::TouchSettings;
 use crate::hotkey_listener::HotkeyListener;
+use crate::migration::HotkeyMigrations;
+use crate::persistence::ConfigFormat;
+use crate::persistence::HotkeyPersistence;
+use crate::profiles::apply_profile_system;
+use crate::profiles::HotkeyProfiles;
+use crate::text_input::TextInputFocus;
 
 #[derive(PartialEq, Eq, Hash, Clone, Debug, SystemLabel)]
 enum HotkeySystems {
@@ -20,10 +50,23 @@ enum HotkeySystems {
     InputReset,
 }
 
+type RegisterAsset<T> = fn(&mut App, &str, &HotkeyConfig<T>, &HotkeyMigrations);
+
 pub struct HotkeyPlugin<T: Eq + Hash + Clone> {
     config: HotkeyConfig<T>,
     key_repeat: KeyRepeatSettings,
+    scroll: ScrollSettings,
+    motion: MotionSettings,
+    touch: TouchSettings,
     listener_settings: Option<(Action, Action)>,
+    persistence: Option<HotkeyPersistence<T>>,
+    config_asset: Option<(String, RegisterAsset<T>)>,
+    migrations: HotkeyMigrations,
+    check_config: Option<fn(&HotkeyConfig<T>)>,
+    profiles: Option<HotkeyProfiles<T>>,
+    text_input_actions: Vec<T>,
+    focus_policy: FocusPolicy,
+    headless_input: bool,
 }
 
 impl<T: Eq + Hash + Clone> HotkeyPlugin<T> {
@@ -31,7 +74,18 @@ impl<T: Eq + Hash + Clone> HotkeyPlugin<T> {
         Self {
             config,
             key_repeat: KeyRepeatSettings::default(),
+            scroll: ScrollSettings::default(),
+            motion: MotionSettings::default(),
+            touch: TouchSettings::default(),
             listener_settings: None,
+            persistence: None,
+            config_asset: None,
+            migrations: HotkeyMigrations::default(),
+            check_config: None,
+            profiles: None,
+            text_input_actions: vec![],
+            focus_policy: FocusPolicy::default(),
+            headless_input: false,
         }
     }
 
@@ -39,33 +93,230 @@ impl<T: Eq + Hash + Clone> HotkeyPlugin<T> {
         self.listener_settings = Some((cancel_action, remove_action));
         self
     }
+
+    /// Manage several named configs through the `HotkeyProfiles` resource.
+    /// With persistence enabled, all profiles are stored in the persistence
+    /// file. Actions missing from a profile are taken from the built-in config.
+    pub fn with_profiles(mut self, profiles: HotkeyProfiles<T>) -> Self {
+        self.profiles = Some(profiles);
+        self
+    }
+
+    /// Repeat settings for actions without their own settings in the config.
+    pub fn with_key_repeat(mut self, key_repeat: KeyRepeatSettings) -> Self {
+        self.key_repeat = key_repeat;
+        self
+    }
+
+    /// Keep the action working while the `TextInputFocus` is set,
+    /// e.g. to send or close the chat.
+    pub fn allow_in_text_input(mut self, action: T) -> Self {
+        self.text_input_actions.push(action);
+        self
+    }
+
+    pub fn with_focus_policy(mut self, focus_policy: FocusPolicy) -> Self {
+        self.focus_policy = focus_policy;
+        self
+    }
+
+    /// Add the input resources and events of bevy's `InputPlugin` and
+    /// `WindowPlugin` for apps without them, e.g. tests with `MinimalPlugins`.
+    /// Must not be combined with those plugins, since their events would
+    /// then be updated twice per frame and get lost.
+    pub fn with_headless_input(mut self) -> Self {
+        self.headless_input = true;
+        self
+    }
+
+    pub fn with_scroll_settings(mut self, scroll: ScrollSettings) -> Self {
+        self.scroll = scroll;
+        self
+    }
+
+    pub fn with_motion_settings(mut self, motion: MotionSettings) -> Self {
+        self.motion = motion;
+        self
+    }
+
+    pub fn with_touch_settings(mut self, touch: TouchSettings) -> Self {
+        self.touch = touch;
+        self
+    }
+
+    /// Migrations applied to configs loaded from files or assets.
+    /// The built-in config is assumed to be up to date.
+    pub fn with_migrations(mut self, migrations: HotkeyMigrations) -> Self {
+        self.config.version = migrations.current_version();
+        self.migrations = migrations;
+        self
+    }
+
+    /// Like `initial_config`, but for all profiles.
+    fn initial_profiles(
+        &self,
+        profiles: &HotkeyProfiles<T>,
+        persistence: Option<&HotkeyPersistence<T>>,
+    ) -> HotkeyProfiles<T> {
+        let saved = persistence.and_then(|persistence| match persistence.load_profiles() {
+            Ok(saved) => saved,
+            Err(err) => match persistence.load() {
+                // Hotkeys saved before profiles were enabled become the active profile.
+                Ok(Some(config)) => {
+                    let mut profiles = profiles.clone();
+                    profiles.insert(profiles.active_name().to_string(), config);
+                    Some(profiles)
+                }
+                _ => {
+                    error!(
+                        "Failed to load hotkey profiles from {}, using defaults: {}",
+                        persistence.path().display(),
+                        err
+                    );
+                    None
+                }
+            },
+        });
+        let mut profiles = saved.unwrap_or_else(|| profiles.clone());
+        profiles.update_from(&self.config);
+        profiles.set_version(self.config.version);
+        profiles
+    }
+
+    /// Returns the config from the persistence file if there is one.
+    /// Actions missing from the file are taken from the built-in config.
+    /// If the file cannot be read, the error is reported and the built-in
+    /// config is used instead.
+    fn initial_config(&self, persistence: Option<&HotkeyPersistence<T>>) -> HotkeyConfig<T> {
+        let persistence = match persistence {
+            Some(persistence) => persistence,
+            None => return self.config.clone(),
+        };
+        match persistence.load() {
+            Ok(Some(mut config)) => {
+                config.update_from(&self.config);
+                config
+            }
+            Ok(None) => self.config.clone(),
+            Err(err) => {
+                error!(
+                    "Failed to load hotkeys from {}, using defaults: {}",
+                    persistence.path().display(),
+                    err
+                );
+                self.config.clone()
+            }
+        }
+    }
+}
+
+impl<T: Eq + Hash + Clone + Serialize + DeserializeOwned> HotkeyPlugin<T> {
+    /// Load the hotkeys from the given file at startup and save them
+    /// to it whenever the listener applies new settings.
+    pub fn persist(mut self, path: impl Into<PathBuf>, format: ConfigFormat) -> Self {
+        self.persistence = Some(HotkeyPersistence::new(path, format));
+        self
+    }
+}
+
+impl<T: Sync + Send + 'static + Eq + Hash + Clone + DeserializeOwned> HotkeyPlugin<T> {
+    /// Load the hotkeys from the given asset path and update them whenever
+    /// the asset changes. Requires the `AssetPlugin` to be added before this
+    /// plugin, otherwise an error is logged and the built-in hotkeys are used.
+    /// The file name has to end in `.hotkeys.json`, `.hotkeys.ron` or `.hotkeys.toml`.
+    /// Hotkeys changed by the player are kept when the asset is reloaded.
+    pub fn load_as_asset(mut self, path: &str) -> Self {
+        self.config_asset = Some((path.into(), register_config_asset::<T>));
+        self
+    }
+}
+
+impl<T: HotkeyActions + Debug> HotkeyPlugin<T> {
+    /// Log a warning at startup for every action without hotkeys.
+    pub fn warn_unbound_actions(mut self) -> Self {
+        self.check_config = Some(warn_unbound_actions::<T>);
+        self
+    }
 }
 
 impl<T: Sync + Send + 'static + Eq + Hash + Clone> Plugin for HotkeyPlugin<T> {
-    fn build(&self, app: &mut bevy::prelude::App) {
-        app.insert_resource(HotkeyStates::from_settings(
-            self.config.clone(),
-            self.key_repeat.clone(),
-        ))
+    fn build(&self, app: &mut App) {
+        let persistence = self
+            .persistence
+            .clone()
+            .map(|persistence| persistence.with_migrations(self.migrations.clone()));
+        let profiles = self
+            .profiles
+            .as_ref()
+            .map(|profiles| self.initial_profiles(profiles, persistence.as_ref()));
+        let config = match &profiles {
+            Some(profiles) => profiles.active().clone(),
+            None => self.initial_config(persistence.as_ref()),
+        };
+        if let Some(check_config) = self.check_config {
+            check_config(&config);
+        }
+        if self.headless_input {
+            add_headless_resource::<Input<KeyCode>>(app);
+            add_headless_resource::<Input<MouseButton>>(app);
+            add_headless_resource::<Touches>(app);
+            add_headless_event::<KeyboardInput>(app);
+            add_headless_event::<MouseWheel>(app);
+            add_headless_event::<MouseMotion>(app);
+            add_headless_event::<ReceivedCharacter>(app);
+        }
+        app.insert_resource(
+            HotkeyStates::from_settings(config.clone(), self.key_repeat.clone())
+                .with_scroll_settings(self.scroll.clone())
+                .with_motion_settings(self.motion.clone())
+                .with_touch_settings(self.touch.clone())
+                .with_text_input_actions(self.text_input_actions.clone()),
+        )
         .init_resource::<WindowFocusState>()
+        .init_resource::<TextInputFocus>()
+        .insert_resource(self.focus_policy)
         .add_system(set_hotkey_states_from_input_system::<T>.label(HotkeySystems::SetHotkeyStates))
         .add_system(
             reset_input_system
                 .label(HotkeySystems::InputReset)
                 .after(HotkeySystems::SetHotkeyStates),
         );
+        if let Some(persistence) = persistence {
+            app.insert_resource(persistence);
+        }
+        if let Some(profiles) = profiles {
+            app.insert_resource(profiles)
+                .add_system(apply_profile_system::<T>.before(HotkeySystems::SetHotkeyStates));
+        }
+        if let Some((path, register)) = &self.config_asset {
+            register(app, path, &self.config, &self.migrations);
+        }
         if let Some((cancel_action, remove_action)) = &self.listener_settings {
             app.insert_resource(HotkeyListener::<T>::new(
                 cancel_action.clone(),
                 remove_action.clone(),
             ))
-            .insert_resource(self.config.clone())
-            .add_system(HotkeyListener::<T>::apply_hotkey_system)
-            .add_system(HotkeyListener::<T>::listen_system);
+            .insert_resource(config)
+            // The listener reads the input, so it has to run before it is cleared.
+            .add_system(HotkeyListener::<T>::apply_hotkey_system.before(HotkeySystems::InputReset))
+            .add_system(HotkeyListener::<T>::listen_system.before(HotkeySystems::InputReset));
         }
     }
 }
 
+fn add_headless_resource<R: Resource + Default>(app: &mut App) {
+    assert!(
+        !app.world.contains_resource::<R>(),
+        "headless input used together with bevy's input plugins"
+    );
+    app.insert_resource(R::default());
+}
+
+fn add_headless_event<E: Resource>(app: &mut App) {
+    add_headless_resource::<Events<E>>(app);
+    app.add_system_to_stage(CoreStage::First, Events::<E>::update_system);
+}
+
 pub fn hotkey_flush_system<T: Sync + Send + 'static + Eq + Hash + Clone>(
     mut hotkeys: ResMut<HotkeyStates<T>>,
 ) {
@@ -1,47 +1,76 @@
 use bevy::core::Time;
-use bevy::input::mouse::MouseWheel;
-use bevy::input::Input;
-use bevy::prelude::KeyCode;
-use bevy::prelude::MouseButton;
+use bevy::math::Vec2;
 
 use super::key_repeat_state::KeyRepeatState;
 use super::Hotkeys;
 use crate::hotkey_config::KeyRepeatSettings;
+use crate::hotkey_input::HotkeyInput;
+use crate::modifier::ModifierMatch;
 
 #[derive(Clone)]
 pub(crate) struct HotkeyState {
     pub just_pressed: bool,
     pub pressed: bool,
     pub just_released: bool,
-    pub repeat_state: KeyRepeatState,
+    /// `None` if the action never repeats.
+    pub repeat_state: Option<KeyRepeatState>,
     pub repeated: bool,
+    /// Repeats fired this frame, not counting the initial press.
+    pub repeats: u32,
+    pub scroll_delta: f32,
+    pub motion_delta: Vec2,
+    pub drag_delta: Vec2,
 }
 
 impl HotkeyState {
-    pub(crate) fn from_settings(key_repeat_settings: &KeyRepeatSettings) -> Self {
+    pub(crate) fn from_settings(key_repeat_settings: Option<&KeyRepeatSettings>) -> Self {
         HotkeyState {
             just_pressed: false,
             pressed: false,
             just_released: false,
-            repeat_state: KeyRepeatState::from_settings(key_repeat_settings),
+            repeat_state: key_repeat_settings.map(KeyRepeatState::from_settings),
             repeated: false,
+            repeats: 0,
+            scroll_delta: 0.0,
+            motion_delta: Vec2::ZERO,
+            drag_delta: Vec2::ZERO,
         }
     }
 
     pub(crate) fn update(
         &mut self,
         hotkey: &Hotkeys,
-        keyboard_input: &Input<KeyCode>,
-        mouse_input: &Input<MouseButton>,
-        mouse_wheel_events: &[&MouseWheel],
+        input: &HotkeyInput,
+        modifier_match: ModifierMatch,
         time: &Time,
     ) {
-        self.just_pressed = hotkey.just_pressed(keyboard_input, mouse_input, mouse_wheel_events);
         let previously_pressed = self.pressed;
-        self.pressed = hotkey.pressed(keyboard_input, mouse_input);
+        self.pressed = hotkey.pressed(input, modifier_match);
+        self.just_pressed = hotkey.just_pressed(input, modifier_match)
+            || (!previously_pressed && hotkey.motion_pressed(input, modifier_match));
+        self.scroll_delta = hotkey.scroll_delta(input, modifier_match);
+        self.motion_delta = hotkey.motion_delta(input, modifier_match);
+        self.drag_delta = hotkey.drag_delta(input, modifier_match);
         self.just_released = previously_pressed && !self.pressed;
-        self.repeat_state.tick(time, self.pressed);
-        self.repeated = self.repeat_state.key_repeated() || self.just_pressed;
+        self.repeats = match &mut self.repeat_state {
+            Some(repeat_state) => {
+                repeat_state.tick(time.delta_seconds(), self.pressed);
+                repeat_state.repeats_this_frame()
+            }
+            None => 0,
+        };
+        self.repeated = self.repeats > 0 || self.just_pressed;
+    }
+
+    /// Release the action as if none of its hotkeys were held.
+    pub(crate) fn release(&mut self) {
+        let previously_pressed = self.pressed;
+        self.reset();
+        self.just_released = previously_pressed;
+    }
+
+    pub(crate) fn set_repeat_settings(&mut self, key_repeat_settings: Option<&KeyRepeatSettings>) {
+        self.repeat_state = key_repeat_settings.map(KeyRepeatState::from_settings);
     }
 
     pub(crate) fn reset(&mut self) {
@@ -49,6 +78,12 @@ impl HotkeyState {
         self.pressed = false;
         self.just_released = false;
         self.repeated = false;
-        self.repeat_state.reset();
+        self.repeats = 0;
+        self.scroll_delta = 0.0;
+        self.motion_delta = Vec2::ZERO;
+        self.drag_delta = Vec2::ZERO;
+        if let Some(repeat_state) = &mut self.repeat_state {
+            repeat_state.reset();
+        }
     }
 }
@@ -2,24 +2,64 @@ use std::collections::HashMap;
 use std::hash::Hash;
 
 use bevy::core::Time;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::mouse::MouseMotion;
 use bevy::input::mouse::MouseWheel;
+use bevy::input::touch::Touches;
+use bevy::input::ElementState;
+use bevy::math::Vec2;
 use bevy::prelude::EventReader;
 use bevy::prelude::Input;
 use bevy::prelude::KeyCode;
 use bevy::prelude::MouseButton;
 use bevy::prelude::Res;
 use bevy::prelude::ResMut;
+use bevy::window::ReceivedCharacter;
+use bevy::window::WindowId;
 use bevy::window::Windows;
 
 use super::hotkey_config::HotkeyConfig;
 use super::hotkey_state::HotkeyState;
 use super::window_focus_state::WindowFocusState;
+use crate::drag_state::DragState;
+use crate::focus_policy::FocusPolicy;
 use crate::hotkey_config::KeyRepeatSettings;
+use crate::hotkey_config::MotionSettings;
+use crate::hotkey_config::ScrollSettings;
+use crate::hotkey_config::TouchSettings;
+use crate::hotkey_input::HotkeyInput;
+use crate::keyboard_layout::KeyboardLayout;
+use crate::text_input::TextInputFocus;
 use crate::Hotkeys;
 
+/// The input of one frame, which `HotkeyStates::update` checks the hotkeys against.
+pub struct FrameInput<'a> {
+    pub keyboard: &'a Input<KeyCode>,
+    pub mouse: &'a Input<MouseButton>,
+    pub keyboard_events: &'a [&'a KeyboardInput],
+    pub mouse_wheel_events: &'a [&'a MouseWheel],
+    pub mouse_motion_events: &'a [&'a MouseMotion],
+    pub touches: &'a Touches,
+    /// The size of the focused window, `None` without a window.
+    pub window_size: Option<Vec2>,
+    pub received_characters: &'a [&'a ReceivedCharacter],
+    pub time: &'a Time,
+}
+
 pub struct HotkeyStates<T: Eq + Hash + Clone> {
     pub(crate) config: HotkeyConfig<T>,
     key_repeat: KeyRepeatSettings,
+    scroll: ScrollSettings,
+    scroll_distance: Vec2,
+    motion: MotionSettings,
+    touch: TouchSettings,
+    drags: DragState,
+    scan_codes: Input<u32>,
+    layout: KeyboardLayout,
+    text_input_focused: bool,
+    text_input_actions: Vec<T>,
+    focused_window: Option<WindowId>,
+    window_scopes: HashMap<T, Vec<WindowId>>,
     states: HashMap<T, HotkeyState>,
 }
 
@@ -28,10 +68,67 @@ impl<T: Eq + Hash + Clone> HotkeyStates<T> {
         Self {
             states: HashMap::new(),
             key_repeat,
+            scroll: ScrollSettings::default(),
+            scroll_distance: Vec2::ZERO,
+            motion: MotionSettings::default(),
+            touch: TouchSettings::default(),
+            drags: DragState::default(),
+            scan_codes: Input::default(),
+            layout: KeyboardLayout::default(),
+            text_input_focused: false,
+            text_input_actions: vec![],
+            focused_window: None,
+            window_scopes: HashMap::new(),
             config,
         }
     }
 
+    pub fn with_scroll_settings(mut self, scroll: ScrollSettings) -> Self {
+        self.scroll = scroll;
+        self
+    }
+
+    pub fn with_motion_settings(mut self, motion: MotionSettings) -> Self {
+        self.motion = motion;
+        self
+    }
+
+    pub fn with_touch_settings(mut self, touch: TouchSettings) -> Self {
+        self.touch = touch;
+        self
+    }
+
+    /// Actions which still work while the `TextInputFocus` is set.
+    pub fn with_text_input_actions(mut self, actions: Vec<T>) -> Self {
+        self.text_input_actions = actions;
+        self
+    }
+
+    /// The labels of the physical keys seen so far, for showing `ScanCode` bindings.
+    pub fn keyboard_layout(&self) -> &KeyboardLayout {
+        &self.layout
+    }
+
+    /// Only trigger the action while one of the windows is focused,
+    /// e.g. for hotkeys of a detached panel.
+    pub fn scope_to_windows(&mut self, name: T, windows: Vec<WindowId>) {
+        self.window_scopes.insert(name, windows);
+    }
+
+    /// Let the action trigger in every window again.
+    pub fn clear_window_scope(&mut self, name: &T) {
+        self.window_scopes.remove(name);
+    }
+
+    fn in_window_scope(&self, name: &T) -> bool {
+        match self.window_scopes.get(name) {
+            Some(windows) => self
+                .focused_window
+                .is_some_and(|focused| windows.contains(&focused)),
+            None => true,
+        }
+    }
+
     pub fn iter_just_pressed(&self) -> impl Iterator<Item = &T> + '_ {
         Box::new(
             self.states
@@ -41,6 +138,53 @@ impl<T: Eq + Hash + Clone> HotkeyStates<T> {
         )
     }
 
+    /// How often the action fired this frame, counting the initial press and
+    /// every repeat. Moving one step per count keeps the speed independent
+    /// of the frame rate.
+    pub fn repeat_count(&self, name: T) -> u32 {
+        self.states
+            .get(&name)
+            .map(|state| state.just_pressed as u32 + state.repeats)
+            .unwrap_or(0)
+    }
+
+    /// How far the scroll bindings of the action were scrolled this frame,
+    /// in the unit of the `ScrollSettings`. Several wheel steps within a
+    /// frame add up.
+    pub fn scroll_delta(&self, name: T) -> f32 {
+        self.states
+            .get(&name)
+            .map(|state| state.scroll_delta)
+            .unwrap_or(0.0)
+    }
+
+    /// How far the mouse moved this frame while a `Motion` or `Drag`
+    /// binding of the action was active. See `drag_delta` for the whole drag.
+    pub fn motion_delta(&self, name: T) -> Vec2 {
+        self.states
+            .get(&name)
+            .map(|state| state.motion_delta)
+            .unwrap_or(Vec2::ZERO)
+    }
+
+    /// How far the mouse moved since the button of an active `Drag` binding
+    /// of the action was pressed, including the motion before the drag started.
+    pub fn drag_delta(&self, name: T) -> Vec2 {
+        self.states
+            .get(&name)
+            .map(|state| state.drag_delta)
+            .unwrap_or(Vec2::ZERO)
+    }
+
+    /// The actions which repeated this frame together with the number of
+    /// repeats, which can be more than one at low frame rates.
+    pub fn iter_repeated(&self) -> impl Iterator<Item = (&T, u32)> + '_ {
+        self.states
+            .iter()
+            .filter(|(_, state)| state.repeats > 0)
+            .map(|(name, state)| (name, state.repeats))
+    }
+
     pub fn flush(&mut self) {
         for (_, state) in self.states.iter_mut() {
             state.reset();
@@ -75,27 +219,66 @@ impl<T: Eq + Hash + Clone> HotkeyStates<T> {
             .unwrap_or(false)
     }
 
-    pub fn update(
-        &mut self,
-        keyboard_input: &Input<KeyCode>,
-        mouse_input: &Input<MouseButton>,
-        mouse_wheel_events: &[&MouseWheel],
-        time: &Time,
-    ) {
+    pub fn update(&mut self, frame: &FrameInput) {
+        self.scan_codes.clear();
+        for event in frame.keyboard_events {
+            self.layout.learn(event);
+            match event.state {
+                ElementState::Pressed => self.scan_codes.press(event.scan_code),
+                ElementState::Released => self.scan_codes.release(event.scan_code),
+            }
+        }
+        let characters: Vec<_> = frame
+            .received_characters
+            .iter()
+            .map(|event| event.char)
+            .collect();
+        let motion = frame
+            .mouse_motion_events
+            .iter()
+            .map(|event| &event.delta)
+            .sum();
+        self.drags
+            .update(frame.mouse, motion, self.motion.drag_threshold);
+        let custom_modifiers = self.config.custom_modifiers();
+        let scroll_deltas: Vec<_> = frame
+            .mouse_wheel_events
+            .iter()
+            .map(|event| self.scroll.delta(event))
+            .collect();
+        let scroll_lines = frame
+            .mouse_wheel_events
+            .iter()
+            .fold(Vec2::ZERO, |lines, event| lines + self.scroll.lines(event));
+        let scroll_steps = self.scroll.steps(&mut self.scroll_distance, scroll_lines);
+        let input = HotkeyInput {
+            keyboard: frame.keyboard,
+            mouse: frame.mouse,
+            scan_codes: &self.scan_codes,
+            scroll_deltas: &scroll_deltas,
+            scroll_steps,
+            motion,
+            motion_threshold: self.motion.threshold,
+            drags: &self.drags,
+            touches: frame.touches,
+            touch_regions: &self.touch.regions,
+            window_size: frame.window_size,
+            characters: &characters,
+            custom_modifiers: &custom_modifiers,
+        };
         for (name, hotkey) in self.config.iter() {
-            let state = self.states.get_mut(name);
-            match state {
-                None => {
-                    self.states
-                        .insert(name.clone(), HotkeyState::from_settings(&self.key_repeat));
-                }
-                Some(state) => state.update(
-                    hotkey,
-                    keyboard_input,
-                    mouse_input,
-                    mouse_wheel_events,
-                    time,
-                ),
+            // New states are updated right away, so that no frame of input is lost.
+            let key_repeat = self.config.repeat_settings(name, &self.key_repeat);
+            let suppressed = (self.text_input_focused && !self.text_input_actions.contains(name))
+                || !self.in_window_scope(name);
+            let state = self
+                .states
+                .entry(name.clone())
+                .or_insert_with(|| HotkeyState::from_settings(key_repeat));
+            if suppressed {
+                state.release();
+            } else {
+                state.update(hotkey, &input, self.config.modifier_match(name), frame.time);
             }
         }
     }
@@ -104,10 +287,38 @@ impl<T: Eq + Hash + Clone> HotkeyStates<T> {
         self.config.map.get(name)
     }
 
+    /// Release all physical keys, like the key codes are released when the
+    /// window loses focus.
+    pub(crate) fn release_scan_codes(&mut self) {
+        let pressed: Vec<_> = self.scan_codes.get_pressed().copied().collect();
+        for scan_code in pressed {
+            self.scan_codes.release(scan_code);
+        }
+    }
+
+    pub(crate) fn set_focused_window(&mut self, window: Option<WindowId>) {
+        self.focused_window = window;
+    }
+
+    pub(crate) fn set_text_input_focused(&mut self, focused: bool) {
+        self.text_input_focused = focused;
+    }
+
+    pub(crate) fn set_config(&mut self, config: HotkeyConfig<T>) {
+        // Drop the states of removed actions, so that they don't stay pressed forever.
+        self.states.retain(|name, _| config.get(name).is_some());
+        for (name, state) in self.states.iter_mut() {
+            state.set_repeat_settings(config.repeat_settings(name, &self.key_repeat));
+        }
+        self.config = config;
+    }
+
     #[cfg(test)]
     pub fn just_press_hotkey(&mut self, name: T) {
-        self.states
-            .insert(name.clone(), HotkeyState::from_settings(&self.key_repeat));
+        self.states.insert(
+            name.clone(),
+            HotkeyState::from_settings(Some(&self.key_repeat)),
+        );
         let state = self.states.get_mut(&name).unwrap();
         state.pressed = true;
         state.just_pressed = true;
@@ -115,34 +326,82 @@ impl<T: Eq + Hash + Clone> HotkeyStates<T> {
 
     #[cfg(test)]
     pub fn press_hotkey(&mut self, name: T) {
-        self.states
-            .insert(name.clone(), HotkeyState::from_settings(&self.key_repeat));
+        self.states.insert(
+            name.clone(),
+            HotkeyState::from_settings(Some(&self.key_repeat)),
+        );
         let state = self.states.get_mut(&name).unwrap();
         state.pressed = true;
     }
 
     #[cfg(test)]
     pub fn release_hotkey(&mut self, name: T) {
-        self.states
-            .insert(name.clone(), HotkeyState::from_settings(&self.key_repeat));
+        self.states.insert(
+            name.clone(),
+            HotkeyState::from_settings(Some(&self.key_repeat)),
+        );
         let state = self.states.get_mut(&name).unwrap();
         state.just_released = true;
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(super) fn set_hotkey_states_from_input_system<T: Sync + Send + 'static + Eq + Hash + Clone>(
     mut hotkey_states: ResMut<HotkeyStates<T>>,
     mut window_focus_state: ResMut<WindowFocusState>,
-    windows: Res<Windows>,
+    focus_policy: Res<FocusPolicy>,
+    windows: Option<Res<Windows>>,
     mut keyboard_input: ResMut<Input<KeyCode>>,
     mouse_input: Res<Input<MouseButton>>,
+    mut keyboard_events: EventReader<KeyboardInput>,
     mut mouse_wheel_events: EventReader<MouseWheel>,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+    touches: Res<Touches>,
+    mut received_characters: EventReader<ReceivedCharacter>,
+    text_input_focus: Option<Res<TextInputFocus>>,
     time: Res<Time>,
 ) {
-    let window = windows.iter().next().unwrap();
-    if window_focus_state.interaction_allowed(window, &keyboard_input, &mouse_input) {
+    hotkey_states.set_text_input_focused(
+        text_input_focus
+            .map(|focus| focus.is_focused())
+            .unwrap_or(false),
+    );
+    // Input is allowed while any window of the app is focused.
+    let focused_window = windows
+        .as_ref()
+        .and_then(|windows| windows.iter().find(|window| window.is_focused()));
+    let window = focused_window.or_else(|| {
+        windows
+            .as_ref()
+            .and_then(|windows| windows.get_primary().or_else(|| windows.iter().next()))
+    });
+    hotkey_states.set_focused_window(focused_window.map(|window| window.id()));
+    let allowed = match (*focus_policy, window) {
+        (FocusPolicy::Ignore, _) => true,
+        (_, Some(_)) => window_focus_state.interaction_allowed(
+            focused_window.is_some(),
+            &keyboard_input,
+            &mouse_input,
+        ),
+        (FocusPolicy::RequireFocus, None) => true,
+        (FocusPolicy::RequireWindow, None) => false,
+    };
+    if allowed {
+        let keyboard_events: Vec<_> = keyboard_events.iter().collect();
         let mouse_wheel_events: Vec<_> = mouse_wheel_events.iter().collect();
-        hotkey_states.update(&keyboard_input, &mouse_input, &mouse_wheel_events, &time);
+        let received_characters: Vec<_> = received_characters.iter().collect();
+        let mouse_motion_events: Vec<_> = mouse_motion_events.iter().collect();
+        hotkey_states.update(&FrameInput {
+            keyboard: &keyboard_input,
+            mouse: &mouse_input,
+            keyboard_events: &keyboard_events,
+            mouse_wheel_events: &mouse_wheel_events,
+            mouse_motion_events: &mouse_motion_events,
+            touches: &touches,
+            window_size: window.map(|window| Vec2::new(window.width(), window.height())),
+            received_characters: &received_characters,
+            time: &time,
+        });
     } else {
         // The following is a (hopefully) temporary fix for a bug in bevy (or winit).
         // This bug will keep any key which was pressed in the moment that the window
@@ -154,6 +413,7 @@ pub(super) fn set_hotkey_states_from_input_system<T: Sync + Send + 'static + Eq
         for key in pressed.into_iter() {
             keyboard_input.release(key);
         }
+        hotkey_states.release_scan_codes();
     }
 }
 
@@ -164,3 +424,158 @@ pub(super) fn reset_input_system(
     keyboard_input.clear();
     mouse_input.clear();
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::mouse::MouseScrollUnit;
+
+    use super::*;
+    use crate::modifier::ModifierMatch;
+
+    #[derive(Default)]
+    struct Frame {
+        keyboard: Input<KeyCode>,
+        mouse: Input<MouseButton>,
+        mouse_wheel_events: Vec<MouseWheel>,
+        received_characters: Vec<ReceivedCharacter>,
+    }
+
+    impl Frame {
+        fn press(&mut self, keys: &[KeyCode]) {
+            self.keyboard.clear();
+            for key in keys {
+                self.keyboard.press(*key);
+            }
+        }
+
+        fn release_all(&mut self) {
+            self.keyboard.clear();
+            let pressed: Vec<_> = self.keyboard.get_pressed().copied().collect();
+            for key in pressed {
+                self.keyboard.release(key);
+            }
+        }
+
+        fn update(&mut self, states: &mut HotkeyStates<&'static str>) {
+            let mouse_wheel_events: Vec<_> = self.mouse_wheel_events.drain(..).collect();
+            let received_characters: Vec<_> = self.received_characters.drain(..).collect();
+            states.update(&FrameInput {
+                keyboard: &self.keyboard,
+                mouse: &self.mouse,
+                keyboard_events: &[],
+                mouse_wheel_events: &mouse_wheel_events.iter().collect::<Vec<_>>(),
+                mouse_motion_events: &[],
+                touches: &Touches::default(),
+                window_size: None,
+                received_characters: &received_characters.iter().collect::<Vec<_>>(),
+                time: &Time::default(),
+            });
+        }
+    }
+
+    fn states(bindings: &[(&'static str, &str)]) -> HotkeyStates<&'static str> {
+        let mut config = HotkeyConfig::empty();
+        for (name, hotkey) in bindings {
+            config.insert_parsed(*name, hotkey).unwrap();
+        }
+        HotkeyStates::from_settings(config, KeyRepeatSettings::default())
+    }
+
+    #[test]
+    fn press_and_release() {
+        let mut states = states(&[("Jump", "Space")]);
+        let mut frame = Frame::default();
+        frame.press(&[KeyCode::Space]);
+        frame.update(&mut states);
+        assert!(states.just_pressed("Jump"));
+        assert_eq!(states.repeat_count("Jump"), 1);
+        frame.keyboard.clear();
+        frame.update(&mut states);
+        assert!(states.pressed("Jump"));
+        assert!(!states.just_pressed("Jump"));
+        assert_eq!(states.repeat_count("Jump"), 0);
+        frame.release_all();
+        frame.update(&mut states);
+        assert!(states.just_released("Jump"));
+        assert!(!states.pressed("Jump"));
+    }
+
+    #[test]
+    fn modifiers_block_exact_bindings() {
+        for modifier in [KeyCode::LControl, KeyCode::LWin] {
+            let mut states = states(&[("Duck", "S"), ("Save", "ctrl+S"), ("Open", "super+S")]);
+            let mut frame = Frame::default();
+            frame.press(&[modifier, KeyCode::S]);
+            frame.update(&mut states);
+            assert!(!states.pressed("Duck"), "{:?}", modifier);
+            assert_eq!(states.pressed("Save"), modifier == KeyCode::LControl);
+            assert_eq!(states.pressed("Open"), modifier == KeyCode::LWin);
+        }
+    }
+
+    #[test]
+    fn subset_matching_allows_extra_modifiers() {
+        let mut states = states(&[("Walk", "W")]);
+        states
+            .config
+            .set_modifier_match("Walk", ModifierMatch::Subset);
+        let mut frame = Frame::default();
+        frame.press(&[KeyCode::LShift, KeyCode::W]);
+        frame.update(&mut states);
+        assert!(states.pressed("Walk"));
+    }
+
+    #[test]
+    fn custom_modifiers_block_their_key() {
+        let mut states = states(&[("Help", "H"), ("LayerHelp", "Space+H"), ("Jump", "Space")]);
+        let mut frame = Frame::default();
+        frame.press(&[KeyCode::Space, KeyCode::H]);
+        frame.update(&mut states);
+        assert!(states.pressed("LayerHelp"));
+        assert!(!states.pressed("Help"));
+        assert!(states.pressed("Jump"));
+    }
+
+    #[test]
+    fn scroll_fires_per_threshold() {
+        let mut states = states(&[("Zoom", "ScrollUp")]).with_scroll_settings(ScrollSettings {
+            threshold: 2.0,
+            ..Default::default()
+        });
+        let mut frame = Frame::default();
+        let line = MouseWheel {
+            unit: MouseScrollUnit::Line,
+            x: 0.0,
+            y: 1.0,
+        };
+        frame.mouse_wheel_events.push(line.clone());
+        frame.update(&mut states);
+        assert!(!states.just_pressed("Zoom"));
+        frame.mouse_wheel_events.push(line);
+        frame.update(&mut states);
+        assert!(states.just_pressed("Zoom"));
+    }
+
+    #[test]
+    fn characters_and_text_input() {
+        let mut states = states(&[("Add", "Char#43"), ("Pause", "Escape")])
+            .with_text_input_actions(vec!["Pause"]);
+        let mut frame = Frame::default();
+        frame.received_characters.push(ReceivedCharacter {
+            id: WindowId::primary(),
+            char: '+',
+        });
+        frame.update(&mut states);
+        assert!(states.just_pressed("Add"));
+
+        states.set_text_input_focused(true);
+        frame.received_characters.push(ReceivedCharacter {
+            id: WindowId::primary(),
+            char: '+',
+        });
+        frame.press(&[KeyCode::Escape]);
+        frame.update(&mut states);
+        assert!(!states.just_pressed("Add"));
+        assert!(states.just_pressed("Pause"));
+    }
+}
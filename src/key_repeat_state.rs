@@ -1,49 +1,137 @@
-use bevy::core::Time;
-use bevy::core::Timer;
-
 use crate::hotkey_config::KeyRepeatSettings;
 
 #[derive(Clone, Debug)]
 pub struct KeyRepeatState {
-    timer: Timer,
-    in_fast_repeat: bool,
-
-    initial_delay: f32,
-    delay: f32,
+    settings: KeyRepeatSettings,
+    // Time since the last repeat (or the key press).
+    elapsed: f32,
+    repeats: u32,
+    repeats_this_frame: u32,
 }
 
 impl KeyRepeatState {
     pub fn from_settings(key_repeat: &KeyRepeatSettings) -> Self {
         Self {
-            timer: Timer::from_seconds(key_repeat.initial_delay, false),
-            in_fast_repeat: false,
-            initial_delay: key_repeat.initial_delay,
-            delay: key_repeat.delay,
+            settings: key_repeat.clone(),
+            elapsed: 0.0,
+            repeats: 0,
+            repeats_this_frame: 0,
         }
     }
 
-    pub fn key_repeated(&mut self) -> bool {
-        let just_finished = self.timer.just_finished();
-        if just_finished {
-            if !self.in_fast_repeat {
-                self.in_fast_repeat = true;
-                self.timer = Timer::from_seconds(self.delay, true);
-            }
-            true
-        } else {
-            false
-        }
+    /// The number of repeats which fired during the last tick. At low frame
+    /// rates this can be more than one.
+    pub fn repeats_this_frame(&self) -> u32 {
+        self.repeats_this_frame
     }
 
-    pub fn tick(&mut self, time: &Time, is_pressed: bool) {
-        self.timer.tick(time.delta());
+    pub fn tick(&mut self, delta: f32, is_pressed: bool) {
         if !is_pressed {
             self.reset();
+            return;
+        }
+        self.repeats_this_frame = 0;
+        self.elapsed += delta;
+        loop {
+            let delay = self.settings.delay_after(self.repeats);
+            // Repeats beyond the limit keep their time and fire next frame.
+            if self.elapsed < delay
+                || Some(self.repeats_this_frame) == self.settings.max_repeats_per_frame
+            {
+                break;
+            }
+            self.elapsed -= delay;
+            self.repeats += 1;
+            self.repeats_this_frame += 1;
         }
     }
 
     pub fn reset(&mut self) {
-        self.timer = Timer::from_seconds(self.initial_delay, false);
-        self.in_fast_repeat = false;
+        self.elapsed = 0.0;
+        self.repeats = 0;
+        self.repeats_this_frame = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hotkey_config::RepeatCurve;
+    use crate::hotkey_config::RepeatStep;
+
+    // Holds the key for `seconds` at the given frame rate and counts the repeats.
+    fn repeats_while_held(settings: &KeyRepeatSettings, seconds: f32, fps: u32) -> u32 {
+        let mut state = KeyRepeatState::from_settings(settings);
+        let mut repeats = 0;
+        for _ in 0..(seconds * fps as f32).round() as u32 {
+            state.tick(1.0 / fps as f32, true);
+            repeats += state.repeats_this_frame();
+        }
+        repeats
+    }
+
+    #[test]
+    fn constant_delay() {
+        let settings = KeyRepeatSettings::new(0.5, 0.1);
+        let mut state = KeyRepeatState::from_settings(&settings);
+        state.tick(0.4, true);
+        assert_eq!(state.repeats_this_frame(), 0);
+        state.tick(0.1, true);
+        assert_eq!(state.repeats_this_frame(), 1);
+        state.tick(0.35, true);
+        assert_eq!(state.repeats_this_frame(), 3);
+    }
+
+    #[test]
+    fn releasing_resets() {
+        let settings = KeyRepeatSettings::new(0.5, 0.1);
+        let mut state = KeyRepeatState::from_settings(&settings);
+        state.tick(0.45, true);
+        state.tick(0.1, false);
+        state.tick(0.1, true);
+        assert_eq!(state.repeats_this_frame(), 0);
+    }
+
+    #[test]
+    fn repeats_are_independent_of_frame_rate() {
+        let curves = [
+            RepeatCurve::Constant,
+            RepeatCurve::Stepped {
+                steps: vec![
+                    RepeatStep {
+                        after: 10,
+                        delay: 0.05,
+                    },
+                    RepeatStep {
+                        after: 30,
+                        delay: 0.02,
+                    },
+                ],
+            },
+            RepeatCurve::Exponential {
+                factor: 0.8,
+                min_delay: 0.02,
+            },
+        ];
+        for curve in curves {
+            let settings = KeyRepeatSettings::new(0.25, 0.1).with_curve(curve);
+            let fast = repeats_while_held(&settings, 3.0, 64);
+            let slow = repeats_while_held(&settings, 3.0, 4);
+            assert_eq!(fast, slow, "{:?}", settings.curve);
+        }
+    }
+
+    #[test]
+    fn max_repeats_per_frame_keeps_the_remaining_time() {
+        let settings = KeyRepeatSettings::new(0.5, 0.1).with_max_repeats_per_frame(2);
+        let mut state = KeyRepeatState::from_settings(&settings);
+        state.tick(1.0, true);
+        assert_eq!(state.repeats_this_frame(), 2);
+        state.tick(0.0, true);
+        assert_eq!(state.repeats_this_frame(), 2);
+        state.tick(0.0, true);
+        assert_eq!(state.repeats_this_frame(), 2);
+        state.tick(0.0, true);
+        assert_eq!(state.repeats_this_frame(), 0);
     }
 }
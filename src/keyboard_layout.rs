@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+
+use bevy::input::keyboard::KeyboardInput;
+use bevy::prelude::KeyCode;
+
+use crate::action::Action;
+use crate::hotkey::Hotkey;
+
+/// The key codes of the physical keys pressed so far, as reported by the
+/// current keyboard layout. Used to label `ScanCode` bindings, e.g. `Scan17`
+/// shows as `W` on QWERTY and as `Z` on AZERTY keyboards.
+#[derive(Clone, Debug, Default)]
+pub struct KeyboardLayout {
+    keys: HashMap<u32, KeyCode>,
+}
+
+impl KeyboardLayout {
+    pub fn key_code(&self, scan_code: u32) -> Option<KeyCode> {
+        self.keys.get(&scan_code).copied()
+    }
+
+    /// The hotkey as shown to the player. Scan codes whose key is not
+    /// known yet are shown as they are stored.
+    pub fn label(&self, hotkey: &Hotkey) -> String {
+        match hotkey.key {
+            Action::ScanCode(scan_code) => match self.key_code(scan_code) {
+                Some(key_code) => Hotkey {
+                    key: Action::Key(key_code),
+                    modifiers: hotkey.modifiers.clone(),
+                }
+                .to_string(),
+                None => hotkey.to_string(),
+            },
+            _ => hotkey.to_string(),
+        }
+    }
+
+    pub(crate) fn learn(&mut self, event: &KeyboardInput) {
+        if let Some(key_code) = event.key_code {
+            self.keys.insert(event.scan_code, key_code);
+        }
+    }
+}
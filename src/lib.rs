@@ -1,25 +1,42 @@
 pub mod action;
+pub mod action_registry;
+pub mod cheat_sheet;
 mod config;
-mod hotkey;
+pub mod config_asset;
+mod drag_state;
+pub mod focus_policy;
+pub mod hotkey;
+pub mod hotkey_actions;
 pub mod hotkey_config;
+mod hotkey_input;
 pub mod hotkey_listener;
 pub mod hotkey_plugin;
 mod hotkey_state;
 pub mod hotkey_states;
 mod key_repeat_state;
+pub mod keyboard_layout;
+pub mod migration;
 pub mod modifier;
-mod mouse_wheel_action;
+pub mod mouse_motion_action;
+pub mod mouse_wheel_action;
+pub mod persistence;
+pub mod presets;
+pub mod profiles;
+mod serialization;
+pub mod text_input;
+pub mod touch_action;
+pub mod validation;
 mod window_focus_state;
 
-use bevy::input::mouse::MouseWheel;
-use bevy::input::Input;
-use bevy::prelude::KeyCode;
-use bevy::prelude::MouseButton;
+use bevy::math::Vec2;
 use hotkey::Hotkey;
+use hotkey_input::HotkeyInput;
+use modifier::ModifierMatch;
 use serde::Deserialize;
 use serde::Serialize;
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(transparent)]
 pub struct Hotkeys(Vec<Hotkey>);
 
 impl Hotkeys {
@@ -31,25 +48,52 @@ impl Hotkeys {
         self.0.push(hotkey)
     }
 
-    pub(crate) fn just_pressed(
-        &self,
-        keyboard_input: &Input<KeyCode>,
-        mouse_input: &Input<MouseButton>,
-        mouse_wheel_events: &[&MouseWheel],
-    ) -> bool {
+    pub(crate) fn just_pressed(&self, input: &HotkeyInput, modifier_match: ModifierMatch) -> bool {
+        self.0
+            .iter()
+            .any(|hotkey| hotkey.just_pressed(input, modifier_match))
+    }
+
+    pub(crate) fn pressed(&self, input: &HotkeyInput, modifier_match: ModifierMatch) -> bool {
+        self.0
+            .iter()
+            .any(|hotkey| hotkey.pressed(input, modifier_match))
+    }
+
+    pub(crate) fn scroll_delta(&self, input: &HotkeyInput, modifier_match: ModifierMatch) -> f32 {
         self.0
             .iter()
-            .any(|hotkey| hotkey.just_pressed(keyboard_input, mouse_input, mouse_wheel_events))
+            .map(|hotkey| hotkey.scroll_delta(input, modifier_match))
+            .sum()
     }
 
-    pub(crate) fn pressed(
+    pub(crate) fn motion_delta(&self, input: &HotkeyInput, modifier_match: ModifierMatch) -> Vec2 {
+        if self
+            .0
+            .iter()
+            .any(|hotkey| hotkey.is_motion() && hotkey.pressed(input, modifier_match))
+        {
+            input.motion
+        } else {
+            Vec2::ZERO
+        }
+    }
+
+    pub(crate) fn motion_pressed(
         &self,
-        keyboard_input: &Input<KeyCode>,
-        mouse_input: &Input<MouseButton>,
+        input: &HotkeyInput,
+        modifier_match: ModifierMatch,
     ) -> bool {
         self.0
             .iter()
-            .any(|hotkey| hotkey.pressed(keyboard_input, mouse_input))
+            .any(|hotkey| hotkey.motion_pressed(input, modifier_match))
+    }
+
+    pub(crate) fn drag_delta(&self, input: &HotkeyInput, modifier_match: ModifierMatch) -> Vec2 {
+        self.0
+            .iter()
+            .find_map(|hotkey| hotkey.drag_delta(input, modifier_match))
+            .unwrap_or(Vec2::ZERO)
     }
 
     pub(crate) fn try_remove_hotkey(&mut self, num: usize) {
@@ -66,7 +110,7 @@ impl Hotkeys {
         }
     }
 
-    pub fn iter(&self) -> std::slice::Iter<Hotkey> {
+    pub fn iter(&self) -> std::slice::Iter<'_, Hotkey> {
         self.0.iter()
     }
 
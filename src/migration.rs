@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use bevy::log::warn;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Deserializer;
+
+use crate::hotkey::Hotkey;
+use crate::hotkey_config::ActionRepeat;
+use crate::hotkey_config::HotkeyConfig;
+use crate::modifier::ModifierMatch;
+use crate::serialization::action_from_str;
+use crate::Hotkeys;
+
+/// A hotkey config as it is stored in a file, before the actions
+/// are converted to the action type. Migrations operate on this.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ConfigDocument {
+    #[serde(default)]
+    pub version: u32,
+    #[serde(deserialize_with = "deserialize_hotkeys")]
+    pub map: HashMap<String, Hotkeys>,
+    #[serde(default)]
+    pub repeat: HashMap<String, ActionRepeat>,
+    #[serde(default)]
+    pub modifier_match: HashMap<String, ModifierMatch>,
+}
+
+impl ConfigDocument {
+    /// Move the hotkeys of `old` to `new`, keeping any hotkeys `new` already has.
+    pub fn rename_action(&mut self, old: &str, new: &str) {
+        if let Some(hotkeys) = self.map.remove(old) {
+            self.map
+                .entry(new.into())
+                .or_insert_with(|| Hotkeys::new(vec![]))
+                .0
+                .extend(hotkeys.0);
+        }
+        copy_setting(&mut self.repeat, old, &[new]);
+        copy_setting(&mut self.modifier_match, old, &[new]);
+    }
+
+    /// Bind each of the `new` actions to the hotkeys of `old`.
+    pub fn split_action(&mut self, old: &str, new: &[&str]) {
+        if let Some(hotkeys) = self.map.remove(old) {
+            for name in new {
+                self.map
+                    .entry(name.to_string())
+                    .or_insert_with(|| Hotkeys::new(vec![]))
+                    .0
+                    .extend(hotkeys.0.iter().cloned());
+            }
+        }
+        copy_setting(&mut self.repeat, old, new);
+        copy_setting(&mut self.modifier_match, old, new);
+    }
+
+    pub fn remove_action(&mut self, name: &str) {
+        self.map.remove(name);
+        self.repeat.remove(name);
+        self.modifier_match.remove(name);
+    }
+
+    /// Actions which do not exist (anymore) are skipped with a warning.
+    pub(crate) fn into_config<T: Eq + Hash + Clone + DeserializeOwned>(self) -> HotkeyConfig<T> {
+        let mut config: HotkeyConfig<T> =
+            convert_actions(self.map, "hotkeys").into_iter().collect();
+        config.repeat = convert_actions(self.repeat, "repeat settings");
+        config.modifier_match = convert_actions(self.modifier_match, "modifier matching");
+        config.version = self.version;
+        config
+    }
+}
+
+// Moves the setting of `old` to each of the `new` actions which don't have one yet.
+fn copy_setting<V: Clone>(settings: &mut HashMap<String, V>, old: &str, new: &[&str]) {
+    if let Some(setting) = settings.remove(old) {
+        for name in new {
+            settings
+                .entry(name.to_string())
+                .or_insert_with(|| setting.clone());
+        }
+    }
+}
+
+fn convert_actions<T: Eq + Hash + DeserializeOwned, V>(
+    map: HashMap<String, V>,
+    what: &str,
+) -> HashMap<T, V> {
+    map.into_iter()
+        .filter_map(|(name, value)| match action_from_str(&name) {
+            Ok(action) => Some((action, value)),
+            Err(_) => {
+                warn!("Skipping {} for unknown action {}", what, name);
+                None
+            }
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredHotkey {
+    Hotkey(Hotkey),
+    Invalid(String),
+}
+
+// Hotkeys which can't be parsed, e.g. ones written by a newer version, are
+// skipped with a warning instead of failing the whole document.
+fn deserialize_hotkeys<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<String, Hotkeys>, D::Error> {
+    let map = HashMap::<String, Vec<StoredHotkey>>::deserialize(deserializer)?;
+    Ok(map
+        .into_iter()
+        .map(|(name, hotkeys)| {
+            let hotkeys = hotkeys
+                .into_iter()
+                .filter_map(|hotkey| match hotkey {
+                    StoredHotkey::Hotkey(hotkey) => Some(hotkey),
+                    StoredHotkey::Invalid(hotkey) => {
+                        warn!("Skipping invalid hotkey {} of action {}", hotkey, name);
+                        None
+                    }
+                })
+                .collect();
+            (name, Hotkeys::new(hotkeys))
+        })
+        .collect())
+}
+
+type Migration = Arc<dyn Fn(&mut ConfigDocument) + Send + Sync>;
+
+/// An ordered list of migrations. The first one upgrades documents from
+/// version 0 (files without a version) to version 1, the second one from
+/// version 1 to version 2 and so on.
+#[derive(Clone, Default)]
+pub struct HotkeyMigrations {
+    migrations: Vec<Migration>,
+}
+
+impl HotkeyMigrations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_migration(
+        mut self,
+        migration: impl Fn(&mut ConfigDocument) + Send + Sync + 'static,
+    ) -> Self {
+        self.migrations.push(Arc::new(migration));
+        self
+    }
+
+    pub fn current_version(&self) -> u32 {
+        self.migrations.len() as u32
+    }
+
+    pub fn migrate(&self, document: &mut ConfigDocument) {
+        if document.version > self.current_version() {
+            warn!(
+                "Hotkey config has version {}, but the newest known version is {}",
+                document.version,
+                self.current_version()
+            );
+            return;
+        }
+        for migration in self.migrations.iter().skip(document.version as usize) {
+            migration(document);
+        }
+        document.version = self.current_version();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hotkeys(hotkeys: &[&str]) -> Hotkeys {
+        Hotkeys::new(
+            hotkeys
+                .iter()
+                .map(|hotkey| hotkey.parse().unwrap())
+                .collect(),
+        )
+    }
+
+    fn document(version: u32, map: &[(&str, &[&str])]) -> ConfigDocument {
+        ConfigDocument {
+            version,
+            map: map
+                .iter()
+                .map(|(name, keys)| (name.to_string(), hotkeys(keys)))
+                .collect(),
+            repeat: HashMap::new(),
+            modifier_match: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn rename_keeps_existing_hotkeys() {
+        let mut document = document(0, &[("Crouch", &["C"]), ("Duck", &["S"])]);
+        document
+            .modifier_match
+            .insert("Crouch".into(), ModifierMatch::Subset);
+        document.rename_action("Crouch", "Duck");
+        assert_eq!(document.map.get("Crouch"), None);
+        assert_eq!(document.map["Duck"], hotkeys(&["S", "C"]));
+        assert_eq!(document.modifier_match["Duck"], ModifierMatch::Subset);
+    }
+
+    #[test]
+    fn split_copies_hotkeys_and_settings() {
+        let mut document = document(0, &[("Walk", &["A", "shift+D"])]);
+        document.repeat.insert("Walk".into(), ActionRepeat::Never);
+        document.split_action("Walk", &["WalkLeft", "WalkRight"]);
+        assert_eq!(document.map.get("Walk"), None);
+        assert_eq!(document.map["WalkLeft"], hotkeys(&["A", "shift+D"]));
+        assert_eq!(document.map["WalkRight"], hotkeys(&["A", "shift+D"]));
+        assert_eq!(document.repeat["WalkLeft"], ActionRepeat::Never);
+        assert_eq!(document.repeat["WalkRight"], ActionRepeat::Never);
+    }
+
+    fn migrations() -> HotkeyMigrations {
+        HotkeyMigrations::new()
+            .add_migration(|document| document.rename_action("Crouch", "Duck"))
+            .add_migration(|document| document.split_action("Walk", &["Left", "Right"]))
+    }
+
+    #[test]
+    fn only_pending_migrations_run() {
+        // Version 1 already uses "Crouch" for something else.
+        let mut document = document(1, &[("Crouch", &["C"]), ("Walk", &["A"])]);
+        migrations().migrate(&mut document);
+        assert_eq!(document.version, 2);
+        assert_eq!(document.map["Crouch"], hotkeys(&["C"]));
+        assert_eq!(document.map["Left"], hotkeys(&["A"]));
+    }
+
+    #[test]
+    fn unversioned_documents_run_every_migration() {
+        let mut document = document(0, &[("Crouch", &["C"]), ("Walk", &["A"])]);
+        migrations().migrate(&mut document);
+        assert_eq!(document.version, 2);
+        assert_eq!(document.map["Duck"], hotkeys(&["C"]));
+        assert_eq!(document.map["Right"], hotkeys(&["A"]));
+    }
+
+    #[test]
+    fn newer_documents_are_left_alone() {
+        let mut document = document(5, &[("Walk", &["A"])]);
+        migrations().migrate(&mut document);
+        assert_eq!(document.version, 5);
+        assert_eq!(document.map["Walk"], hotkeys(&["A"]));
+    }
+
+    #[test]
+    fn unknown_actions_are_skipped() {
+        #[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Deserialize)]
+        enum TestAction {
+            Jump,
+        }
+        let config: HotkeyConfig<TestAction> =
+            document(3, &[("Jump", &["Space"]), ("Fly", &["F"])]).into_config();
+        assert_eq!(config.get(&TestAction::Jump), Some(&hotkeys(&["Space"])));
+        assert_eq!(config.iter().count(), 1);
+        assert_eq!(config.version(), 3);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn invalid_hotkeys_are_skipped() {
+        let document: ConfigDocument =
+            serde_json::from_str(r#"{"map": {"Jump": ["Space", "hyper+J", "W"]}}"#).unwrap();
+        assert_eq!(document.version, 0);
+        assert_eq!(document.map["Jump"], hotkeys(&["Space", "W"]));
+    }
+}
@@ -1,12 +1,37 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use bevy::input::Input;
 use bevy::prelude::KeyCode;
+use bevy::prelude::MouseButton;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::action::Action;
+use crate::action::ParseHotkeyError;
+
+/// `Control`, `Shift`, `Alt` and `Super` match either side of the keyboard,
+/// `LControl` to `RSuper` only the left or right key. `Key` and `Button`
+/// turn any other key or mouse button into a modifier, e.g. `Space` as a
+/// layer key or holding the right mouse button while scrolling.
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 pub enum Modifier {
     Control,
     Shift,
     Alt,
+    /// The Windows key, or Command on Mac.
+    Super,
+    LControl,
+    RControl,
+    LShift,
+    RShift,
+    LAlt,
+    /// Also known as AltGr.
+    RAlt,
+    LSuper,
+    RSuper,
+    Key(KeyCode),
+    Button(MouseButton),
 }
 
 impl Modifier {
@@ -19,28 +44,198 @@ impl Modifier {
             Modifier::Control => vec![KeyCode::LControl, KeyCode::RControl],
             Modifier::Shift => vec![KeyCode::LShift, KeyCode::RShift],
             Modifier::Alt => vec![KeyCode::LAlt, KeyCode::RAlt],
+            Modifier::Super => vec![KeyCode::LWin, KeyCode::RWin],
+            Modifier::LControl => vec![KeyCode::LControl],
+            Modifier::RControl => vec![KeyCode::RControl],
+            Modifier::LShift => vec![KeyCode::LShift],
+            Modifier::RShift => vec![KeyCode::RShift],
+            Modifier::LAlt => vec![KeyCode::LAlt],
+            Modifier::RAlt => vec![KeyCode::RAlt],
+            Modifier::LSuper => vec![KeyCode::LWin],
+            Modifier::RSuper => vec![KeyCode::RWin],
+            Modifier::Key(key_code) => vec![*key_code],
+            Modifier::Button(_) => vec![],
         }
     }
 
-    pub fn from_key_code(key_code: &KeyCode) -> Option<Self> {
-        match key_code {
-            KeyCode::LControl => Some(Self::Control),
-            KeyCode::RControl => Some(Self::Control),
-            KeyCode::LShift => Some(Self::Shift),
-            KeyCode::RShift => Some(Self::Shift),
-            KeyCode::LAlt => Some(Self::Alt),
-            KeyCode::RAlt => Some(Self::Alt),
-            _ => None,
+    pub(crate) fn pressed(
+        &self,
+        keyboard_input: &Input<KeyCode>,
+        mouse_input: &Input<MouseButton>,
+    ) -> bool {
+        match self {
+            Modifier::Button(button) => mouse_input.pressed(*button),
+            _ => self
+                .get_key_codes()
+                .iter()
+                .any(|key_code| keyboard_input.pressed(*key_code)),
         }
     }
 
-    pub fn to_str(&self) -> &str {
+    /// Whether this is a `Key` or `Button` modifier.
+    pub fn is_custom(&self) -> bool {
+        matches!(self, Modifier::Key(_) | Modifier::Button(_))
+    }
+
+    /// The modifier matching either side, e.g. `Shift` for `LShift`.
+    /// `None` for custom modifiers.
+    pub fn generic(&self) -> Option<Self> {
         match self {
+            Modifier::Control | Modifier::LControl | Modifier::RControl => Some(Modifier::Control),
+            Modifier::Shift | Modifier::LShift | Modifier::RShift => Some(Modifier::Shift),
+            Modifier::Alt | Modifier::LAlt | Modifier::RAlt => Some(Modifier::Alt),
+            Modifier::Super | Modifier::LSuper | Modifier::RSuper => Some(Modifier::Super),
+            Modifier::Key(_) | Modifier::Button(_) => None,
+        }
+    }
+
+    /// The left and right variants of this modifier.
+    pub fn sides(&self) -> Option<(Self, Self)> {
+        match self.generic()? {
+            Modifier::Control => Some((Modifier::LControl, Modifier::RControl)),
+            Modifier::Shift => Some((Modifier::LShift, Modifier::RShift)),
+            Modifier::Alt => Some((Modifier::LAlt, Modifier::RAlt)),
+            _ => Some((Modifier::LSuper, Modifier::RSuper)),
+        }
+    }
+
+    /// Returns the generic modifier, since bindings usually shouldn't
+    /// depend on the side.
+    pub fn from_key_code(key_code: &KeyCode) -> Option<Self> {
+        GENERIC_MODIFIERS
+            .iter()
+            .find(|modifier| modifier.is(key_code))
+            .cloned()
+    }
+}
+
+impl Display for Modifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
             Modifier::Control => "ctrl",
             Modifier::Shift => "shift",
             Modifier::Alt => "alt",
+            Modifier::Super => "super",
+            Modifier::LControl => "lctrl",
+            Modifier::RControl => "rctrl",
+            Modifier::LShift => "lshift",
+            Modifier::RShift => "rshift",
+            Modifier::LAlt => "lalt",
+            Modifier::RAlt => "ralt",
+            Modifier::LSuper => "lsuper",
+            Modifier::RSuper => "rsuper",
+            Modifier::Key(key_code) => return Action::Key(*key_code).fmt(f),
+            Modifier::Button(button) => return Action::Button(*button).fmt(f),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How the held modifiers are compared to those of a binding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ModifierMatch {
+    /// Exactly the modifiers of the binding have to be held,
+    /// so `W` is not triggered by `ctrl+W`.
+    #[default]
+    Exact,
+    /// At least the modifiers of the binding have to be held,
+    /// so `W` still moves while sprinting with shift.
+    Subset,
+    /// Modifiers are not checked at all.
+    Ignore,
+}
+
+impl FromStr for Modifier {
+    type Err = ParseHotkeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("meta") {
+            return Ok(Modifier::Super);
+        }
+        if s.eq_ignore_ascii_case("altgr") {
+            return Ok(Modifier::RAlt);
+        }
+        if let Some(modifier) = AVAILABLE_MODIFIERS
+            .iter()
+            .find(|modifier| modifier.to_string().eq_ignore_ascii_case(s))
+        {
+            return Ok(modifier.clone());
+        }
+        match s.parse()? {
+            // Modifier keys written as key names, e.g. "LShift", become side-specific modifiers.
+            Action::Key(key_code) => Ok(AVAILABLE_MODIFIERS
+                .iter()
+                .find(|modifier| modifier.get_key_codes() == [key_code])
+                .cloned()
+                .unwrap_or(Modifier::Key(key_code))),
+            Action::Button(button) => Ok(Modifier::Button(button)),
+            Action::Scroll(_)
+            | Action::Motion(_)
+            | Action::Drag(_)
+            | Action::ScanCode(_)
+            | Action::Touch(_)
+            | Action::Char(_) => Err(ParseHotkeyError(s.into())),
         }
     }
 }
 
-pub const AVAILABLE_MODIFIERS: &[Modifier] = &[Modifier::Control, Modifier::Shift, Modifier::Alt];
+pub const GENERIC_MODIFIERS: &[Modifier] = &[
+    Modifier::Control,
+    Modifier::Shift,
+    Modifier::Alt,
+    Modifier::Super,
+];
+
+// Also the order in which modifiers are displayed.
+pub const AVAILABLE_MODIFIERS: &[Modifier] = &[
+    Modifier::Control,
+    Modifier::LControl,
+    Modifier::RControl,
+    Modifier::Shift,
+    Modifier::LShift,
+    Modifier::RShift,
+    Modifier::Alt,
+    Modifier::LAlt,
+    Modifier::RAlt,
+    Modifier::Super,
+    Modifier::LSuper,
+    Modifier::RSuper,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Modifier {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn names_and_aliases() {
+        assert_eq!(parse("Ctrl"), Modifier::Control);
+        assert_eq!(parse("SHIFT"), Modifier::Shift);
+        assert_eq!(parse("meta"), Modifier::Super);
+        assert_eq!(parse("AltGr"), Modifier::RAlt);
+        assert_eq!(parse("LShift"), Modifier::LShift);
+        assert_eq!(parse("Space"), Modifier::Key(KeyCode::Space));
+        assert_eq!(parse("MouseRight"), Modifier::Button(MouseButton::Right));
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for modifier in AVAILABLE_MODIFIERS {
+            assert_eq!(&parse(&modifier.to_string()), modifier);
+        }
+        assert_eq!(
+            parse(&Modifier::Key(KeyCode::Tab).to_string()),
+            Modifier::Key(KeyCode::Tab)
+        );
+    }
+
+    #[test]
+    fn non_button_actions_are_not_modifiers() {
+        for s in ["ScrollUp", "Scan17", "Tap", "CharA", "DragMouseLeft"] {
+            assert!(s.parse::<Modifier>().is_err(), "{}", s);
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use bevy::math::Vec2;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum MouseMotionAction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl MouseMotionAction {
+    /// The part of the motion in this direction, or zero.
+    /// As in bevy, positive values move down and to the right.
+    pub(crate) fn delta(&self, delta: Vec2) -> f32 {
+        match self {
+            MouseMotionAction::Up => (-delta.y).max(0.0),
+            MouseMotionAction::Down => delta.y.max(0.0),
+            MouseMotionAction::Left => (-delta.x).max(0.0),
+            MouseMotionAction::Right => delta.x.max(0.0),
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use bevy::math::Vec2;
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -5,4 +6,31 @@ use serde::Serialize;
 pub enum MouseWheelAction {
     Up,
     Down,
+    Left,
+    Right,
+}
+
+impl MouseWheelAction {
+    /// The part of the scroll delta in this direction, or zero.
+    /// As in winit, positive values scroll up and to the left.
+    pub(crate) fn delta(&self, delta: Vec2) -> f32 {
+        match self {
+            MouseWheelAction::Up => delta.y.max(0.0),
+            MouseWheelAction::Down => (-delta.y).max(0.0),
+            MouseWheelAction::Left => delta.x.max(0.0),
+            MouseWheelAction::Right => (-delta.x).max(0.0),
+        }
+    }
+
+    /// The direction of a scroll delta, preferring vertical scrolling.
+    pub(crate) fn from_delta(delta: Vec2) -> Option<Self> {
+        [
+            MouseWheelAction::Up,
+            MouseWheelAction::Down,
+            MouseWheelAction::Left,
+            MouseWheelAction::Right,
+        ]
+        .into_iter()
+        .find(|action| action.delta(delta) > 0.0)
+    }
 }
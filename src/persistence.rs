@@ -0,0 +1,247 @@
+use std::fmt::Display;
+use std::fs;
+use std::hash::Hash;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::hotkey_config::HotkeyConfig;
+use crate::migration::HotkeyMigrations;
+use crate::profiles::HotkeyProfiles;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigFormat {
+    #[cfg(feature = "json")]
+    Json,
+    #[cfg(feature = "ron")]
+    Ron,
+    #[cfg(feature = "toml")]
+    Toml,
+}
+
+impl ConfigFormat {
+    /// All formats enabled via cargo features.
+    pub const ALL: &'static [ConfigFormat] = &[
+        #[cfg(feature = "json")]
+        ConfigFormat::Json,
+        #[cfg(feature = "ron")]
+        ConfigFormat::Ron,
+        #[cfg(feature = "toml")]
+        ConfigFormat::Toml,
+    ];
+
+    pub fn extension(&self) -> &'static str {
+        match *self {
+            #[cfg(feature = "json")]
+            ConfigFormat::Json => "json",
+            #[cfg(feature = "ron")]
+            ConfigFormat::Ron => "ron",
+            #[cfg(feature = "toml")]
+            ConfigFormat::Toml => "toml",
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|format| format.extension().eq_ignore_ascii_case(extension))
+            .copied()
+    }
+}
+
+#[derive(Debug)]
+pub enum PersistenceError {
+    Io(io::Error),
+    Parse(String),
+    Serialize(String),
+}
+
+impl Display for PersistenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PersistenceError::Io(err) => write!(f, "io error: {}", err),
+            PersistenceError::Parse(err) => write!(f, "invalid hotkey config: {}", err),
+            PersistenceError::Serialize(err) => {
+                write!(f, "could not serialize hotkey config: {}", err)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PersistenceError {}
+
+// Only used by the format features.
+#[cfg(any(feature = "json", feature = "ron", feature = "toml"))]
+impl PersistenceError {
+    pub(crate) fn parse(err: impl Display) -> Self {
+        PersistenceError::Parse(err.to_string())
+    }
+
+    pub(crate) fn serialize(err: impl Display) -> Self {
+        PersistenceError::Serialize(err.to_string())
+    }
+}
+
+impl From<io::Error> for PersistenceError {
+    fn from(err: io::Error) -> Self {
+        PersistenceError::Io(err)
+    }
+}
+
+/// Where and in which format the hotkey configuration is stored on disk.
+/// Inserted as a resource by the `HotkeyPlugin` if persistence is enabled.
+pub struct HotkeyPersistence<T: Eq + Hash + Clone> {
+    path: PathBuf,
+    format: ConfigFormat,
+    migrations: HotkeyMigrations,
+    load: fn(&str, ConfigFormat, &HotkeyMigrations) -> Result<HotkeyConfig<T>, PersistenceError>,
+    save: fn(&HotkeyConfig<T>, ConfigFormat, &HotkeyMigrations) -> Result<String, PersistenceError>,
+    load_profiles:
+        fn(&str, ConfigFormat, &HotkeyMigrations) -> Result<HotkeyProfiles<T>, PersistenceError>,
+    save_profiles:
+        fn(&HotkeyProfiles<T>, ConfigFormat, &HotkeyMigrations) -> Result<String, PersistenceError>,
+}
+
+impl<T: Eq + Hash + Clone> Clone for HotkeyPersistence<T> {
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            format: self.format,
+            migrations: self.migrations.clone(),
+            load: self.load,
+            save: self.save,
+            load_profiles: self.load_profiles,
+            save_profiles: self.save_profiles,
+        }
+    }
+}
+
+impl<T: Eq + Hash + Clone + Serialize + DeserializeOwned> HotkeyPersistence<T> {
+    pub fn new(path: impl Into<PathBuf>, format: ConfigFormat) -> Self {
+        Self {
+            path: path.into(),
+            format,
+            migrations: HotkeyMigrations::default(),
+            load: HotkeyConfig::load_from_str_with_migrations,
+            save: HotkeyConfig::save_to_string_with_migrations,
+            load_profiles: HotkeyProfiles::load_from_str_with_migrations,
+            save_profiles: HotkeyProfiles::save_to_string_with_migrations,
+        }
+    }
+}
+
+impl<T: Eq + Hash + Clone> HotkeyPersistence<T> {
+    pub fn with_migrations(mut self, migrations: HotkeyMigrations) -> Self {
+        self.migrations = migrations;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn format(&self) -> ConfigFormat {
+        self.format
+    }
+
+    /// Returns `Ok(None)` if there is no saved configuration yet.
+    pub fn load(&self) -> Result<Option<HotkeyConfig<T>>, PersistenceError> {
+        match self.read()? {
+            Some(contents) => (self.load)(&contents, self.format, &self.migrations).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn save(&self, config: &HotkeyConfig<T>) -> Result<(), PersistenceError> {
+        let contents = (self.save)(config, self.format, &self.migrations)?;
+        write_atomically(&self.path, contents.as_bytes())?;
+        Ok(())
+    }
+
+    /// Like `load`, but for a file containing all profiles.
+    pub fn load_profiles(&self) -> Result<Option<HotkeyProfiles<T>>, PersistenceError> {
+        match self.read()? {
+            Some(contents) => {
+                (self.load_profiles)(&contents, self.format, &self.migrations).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn save_profiles(&self, profiles: &HotkeyProfiles<T>) -> Result<(), PersistenceError> {
+        let contents = (self.save_profiles)(profiles, self.format, &self.migrations)?;
+        write_atomically(&self.path, contents.as_bytes())?;
+        Ok(())
+    }
+
+    fn read(&self) -> Result<Option<String>, PersistenceError> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+// Without any format features enabled, ConfigFormat has no variants.
+#[cfg_attr(
+    not(any(feature = "json", feature = "ron", feature = "toml")),
+    allow(unused_variables)
+)]
+pub(crate) fn parse_str<D: DeserializeOwned>(
+    contents: &str,
+    format: ConfigFormat,
+) -> Result<D, PersistenceError> {
+    match format {
+        #[cfg(feature = "json")]
+        ConfigFormat::Json => serde_json::from_str(contents).map_err(PersistenceError::parse),
+        #[cfg(feature = "ron")]
+        ConfigFormat::Ron => ron::from_str(contents).map_err(PersistenceError::parse),
+        #[cfg(feature = "toml")]
+        ConfigFormat::Toml => toml::from_str(contents).map_err(PersistenceError::parse),
+    }
+}
+
+#[cfg_attr(
+    not(any(feature = "json", feature = "ron", feature = "toml")),
+    allow(unused_variables)
+)]
+pub(crate) fn to_string<S: Serialize>(
+    value: &S,
+    format: ConfigFormat,
+) -> Result<String, PersistenceError> {
+    match format {
+        #[cfg(feature = "json")]
+        ConfigFormat::Json => {
+            serde_json::to_string_pretty(value).map_err(PersistenceError::serialize)
+        }
+        #[cfg(feature = "ron")]
+        ConfigFormat::Ron => ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+            .map_err(PersistenceError::serialize),
+        #[cfg(feature = "toml")]
+        ConfigFormat::Toml => toml::to_string_pretty(value).map_err(PersistenceError::serialize),
+    }
+}
+
+/// Write to a temporary file next to the target first and rename it afterwards,
+/// so that a crash during writing never leaves a half-written config behind.
+fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+    {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+    fs::rename(&temp_path, path)
+}
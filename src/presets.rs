@@ -0,0 +1,208 @@
+use std::hash::Hash;
+
+use bevy::prelude::KeyCode;
+
+use crate::hotkey_config::HotkeyConfig;
+use crate::validation::HotkeyConflict;
+
+/// Named alternative configs the player can choose from, such as the
+/// movement layouts of `HotkeyPresets::movement`. Can be inserted as a
+/// resource to list them in a settings menu.
+#[derive(Clone, Debug)]
+pub struct HotkeyPresets<T: Eq + Hash + Clone> {
+    presets: Vec<(String, HotkeyConfig<T>)>,
+}
+
+impl<T: Eq + Hash + Clone> Default for HotkeyPresets<T> {
+    fn default() -> Self {
+        Self { presets: vec![] }
+    }
+}
+
+impl<T: Eq + Hash + Clone> HotkeyPresets<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in `MovementKeys` layouts for the given actions.
+    pub fn movement(up: T, left: T, down: T, right: T) -> Self {
+        MovementKeys::ALL.iter().fold(Self::new(), |presets, keys| {
+            presets.with(
+                keys.name(),
+                keys.config(up.clone(), left.clone(), down.clone(), right.clone()),
+            )
+        })
+    }
+
+    /// Add a preset or replace the one with the same name.
+    pub fn insert(&mut self, name: impl Into<String>, config: HotkeyConfig<T>) {
+        let name = name.into();
+        match self.presets.iter_mut().find(|(other, _)| *other == name) {
+            Some((_, preset)) => *preset = config,
+            None => self.presets.push((name, config)),
+        }
+    }
+
+    pub fn with(mut self, name: impl Into<String>, config: HotkeyConfig<T>) -> Self {
+        self.insert(name, config);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&HotkeyConfig<T>> {
+        self.presets
+            .iter()
+            .find(|(other, _)| other == name)
+            .map(|(_, config)| config)
+    }
+
+    /// The names of all presets in the order they were added.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.presets.iter().map(|(name, _)| name.as_str())
+    }
+}
+
+/// Common keyboard layouts for moving in four directions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MovementKeys {
+    Wasd,
+    Esdf,
+    Arrows,
+}
+
+impl MovementKeys {
+    pub const ALL: &'static [MovementKeys] =
+        &[MovementKeys::Wasd, MovementKeys::Esdf, MovementKeys::Arrows];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MovementKeys::Wasd => "WASD",
+            MovementKeys::Esdf => "ESDF",
+            MovementKeys::Arrows => "Arrows",
+        }
+    }
+
+    /// The keys for up, left, down and right.
+    pub fn keys(&self) -> [KeyCode; 4] {
+        match self {
+            MovementKeys::Wasd => [KeyCode::W, KeyCode::A, KeyCode::S, KeyCode::D],
+            MovementKeys::Esdf => [KeyCode::E, KeyCode::S, KeyCode::D, KeyCode::F],
+            MovementKeys::Arrows => [KeyCode::Up, KeyCode::Left, KeyCode::Down, KeyCode::Right],
+        }
+    }
+
+    pub fn config<T: Eq + Hash + Clone>(
+        &self,
+        up: T,
+        left: T,
+        down: T,
+        right: T,
+    ) -> HotkeyConfig<T> {
+        let mut config = HotkeyConfig::empty();
+        for (name, key_code) in [up, left, down, right].into_iter().zip(self.keys()) {
+            config.insert_normal(name, key_code);
+        }
+        config
+    }
+}
+
+impl<T: Eq + Hash + Clone> HotkeyConfig<T> {
+    /// Replace the hotkeys and settings of every action in the preset,
+    /// keeping all other actions as they are. Unlike `update_from`, this
+    /// overwrites existing bindings. Returns the hotkeys which are now bound
+    /// to an action of the preset and to some other action.
+    pub fn apply_preset(&mut self, preset: &HotkeyConfig<T>) -> Vec<HotkeyConflict<T>> {
+        for (name, hotkeys) in preset.map.iter() {
+            self.map.insert(name.clone(), hotkeys.clone());
+        }
+        for (name, repeat) in preset.repeat.iter() {
+            self.repeat.insert(name.clone(), repeat.clone());
+        }
+        for (name, modifier_match) in preset.modifier_match.iter() {
+            self.modifier_match.insert(name.clone(), *modifier_match);
+        }
+        self.validate()
+            .conflicts
+            .into_iter()
+            .filter(|conflict| {
+                conflict
+                    .actions
+                    .iter()
+                    .any(|name| preset.map.contains_key(name))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn movement() -> HotkeyConfig<&'static str> {
+        MovementKeys::Wasd.config("Up", "Left", "Down", "Right")
+    }
+
+    #[test]
+    fn apply_without_conflicts() {
+        let mut config = movement();
+        config.insert_normal("Jump", KeyCode::Space);
+        let conflicts =
+            config.apply_preset(&MovementKeys::Arrows.config("Up", "Left", "Down", "Right"));
+        assert!(conflicts.is_empty());
+        assert_eq!(config.get(&"Up").unwrap().iter().count(), 1);
+        assert_eq!(
+            config
+                .get(&"Up")
+                .unwrap()
+                .iter()
+                .next()
+                .unwrap()
+                .to_string(),
+            "Up"
+        );
+        assert!(config.get(&"Jump").is_some());
+    }
+
+    #[test]
+    fn apply_reports_conflicts_with_other_actions() {
+        let mut config = movement();
+        config.insert_normal("Inventory", KeyCode::E);
+        config.insert_normal("Sneak", KeyCode::F);
+        let conflicts =
+            config.apply_preset(&MovementKeys::Esdf.config("Up", "Left", "Down", "Right"));
+        let mut conflicts: Vec<_> = conflicts
+            .into_iter()
+            .map(|conflict| {
+                let mut actions = conflict.actions;
+                actions.sort_unstable();
+                (conflict.hotkey.to_string(), actions)
+            })
+            .collect();
+        conflicts.sort_unstable();
+        assert_eq!(
+            conflicts,
+            [
+                ("E".to_string(), vec!["Inventory", "Up"]),
+                ("F".to_string(), vec!["Right", "Sneak"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn movement_presets() {
+        let presets = HotkeyPresets::movement("Up", "Left", "Down", "Right");
+        assert_eq!(
+            presets.names().collect::<Vec<_>>(),
+            ["WASD", "ESDF", "Arrows"]
+        );
+        let esdf = presets.get("ESDF").unwrap();
+        assert_eq!(
+            esdf.get(&"Left")
+                .unwrap()
+                .iter()
+                .next()
+                .unwrap()
+                .to_string(),
+            "S"
+        );
+    }
+}
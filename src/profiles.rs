@@ -0,0 +1,395 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::hash::Hash;
+
+use bevy::log::error;
+use bevy::prelude::Res;
+use bevy::prelude::ResMut;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::hotkey_config::HotkeyConfig;
+use crate::hotkey_config::VersionedConfig;
+use crate::hotkey_states::HotkeyStates;
+use crate::migration::ConfigDocument;
+use crate::migration::HotkeyMigrations;
+use crate::persistence::parse_str;
+use crate::persistence::to_string;
+use crate::persistence::ConfigFormat;
+use crate::persistence::HotkeyPersistence;
+use crate::persistence::PersistenceError;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ProfileError {
+    NotFound(String),
+    AlreadyExists(String),
+    /// The active profile cannot be deleted.
+    Active(String),
+}
+
+impl Display for ProfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProfileError::NotFound(name) => write!(f, "no hotkey profile named {}", name),
+            ProfileError::AlreadyExists(name) => {
+                write!(f, "a hotkey profile named {} already exists", name)
+            }
+            ProfileError::Active(name) => {
+                write!(f, "the active hotkey profile {} cannot be deleted", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+/// Named hotkey configs of which one is active, e.g. for several players
+/// sharing a machine. Inserted as a resource by the `HotkeyPlugin` if
+/// profiles are enabled. Switching the active profile updates the
+/// `HotkeyStates` and all changes are saved if persistence is enabled.
+#[derive(Clone, Debug)]
+pub struct HotkeyProfiles<T: Eq + Hash + Clone> {
+    profiles: BTreeMap<String, HotkeyConfig<T>>,
+    active: String,
+    active_changed: bool,
+    modified: bool,
+}
+
+impl<T: Eq + Hash + Clone> HotkeyProfiles<T> {
+    pub fn new(name: impl Into<String>, config: HotkeyConfig<T>) -> Self {
+        let name = name.into();
+        Self {
+            profiles: BTreeMap::from([(name.clone(), config)]),
+            active: name,
+            active_changed: false,
+            modified: false,
+        }
+    }
+
+    pub fn active_name(&self) -> &str {
+        &self.active
+    }
+
+    pub fn active(&self) -> &HotkeyConfig<T> {
+        &self.profiles[&self.active]
+    }
+
+    pub fn get(&self, name: &str) -> Option<&HotkeyConfig<T>> {
+        self.profiles.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.profiles.contains_key(name)
+    }
+
+    /// The names of all profiles in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    /// Add a profile or replace an existing one. The config is assumed to be
+    /// as new as the other profiles, so that it isn't migrated once loaded.
+    pub fn insert(&mut self, name: impl Into<String>, mut config: HotkeyConfig<T>) {
+        let name = name.into();
+        config.version = config.version.max(self.active().version);
+        if name == self.active {
+            self.active_changed = true;
+        }
+        self.profiles.insert(name, config);
+        self.modified = true;
+    }
+
+    pub fn set_active(&mut self, name: &str) -> Result<(), ProfileError> {
+        if !self.contains(name) {
+            return Err(ProfileError::NotFound(name.into()));
+        }
+        if self.active != name {
+            self.active = name.into();
+            self.active_changed = true;
+            self.modified = true;
+        }
+        Ok(())
+    }
+
+    /// Add a copy of the profile `from` named `to`.
+    pub fn duplicate(&mut self, from: &str, to: impl Into<String>) -> Result<(), ProfileError> {
+        let to = to.into();
+        if self.contains(&to) {
+            return Err(ProfileError::AlreadyExists(to));
+        }
+        let config = self
+            .get(from)
+            .cloned()
+            .ok_or_else(|| ProfileError::NotFound(from.into()))?;
+        self.profiles.insert(to, config);
+        self.modified = true;
+        Ok(())
+    }
+
+    pub fn rename(&mut self, old: &str, new: impl Into<String>) -> Result<(), ProfileError> {
+        let new = new.into();
+        if old == new && self.contains(old) {
+            return Ok(());
+        }
+        if self.contains(&new) {
+            return Err(ProfileError::AlreadyExists(new));
+        }
+        let config = self
+            .profiles
+            .remove(old)
+            .ok_or_else(|| ProfileError::NotFound(old.into()))?;
+        if self.active == old {
+            self.active = new.clone();
+        }
+        self.profiles.insert(new, config);
+        self.modified = true;
+        Ok(())
+    }
+
+    pub fn delete(&mut self, name: &str) -> Result<(), ProfileError> {
+        if self.active == name {
+            return Err(ProfileError::Active(name.into()));
+        }
+        self.profiles
+            .remove(name)
+            .ok_or_else(|| ProfileError::NotFound(name.into()))?;
+        self.modified = true;
+        Ok(())
+    }
+
+    /// Fill in the actions missing from any profile.
+    pub(crate) fn update_from(&mut self, defaults: &HotkeyConfig<T>) {
+        for config in self.profiles.values_mut() {
+            config.update_from(defaults);
+        }
+    }
+
+    /// Mark all profiles as up to date, since only the configs loaded from
+    /// files can be older than the plugin.
+    pub(crate) fn set_version(&mut self, version: u32) {
+        for config in self.profiles.values_mut() {
+            config.version = version;
+        }
+    }
+
+    /// Store hotkeys which are already applied, such as those of the listener.
+    pub(crate) fn update_active(&mut self, config: HotkeyConfig<T>) {
+        self.profiles.insert(self.active.clone(), config);
+    }
+}
+
+#[derive(Serialize)]
+struct SerializedProfiles<'a, T: Eq + Hash + Clone + Serialize> {
+    active: &'a str,
+    profiles: BTreeMap<&'a String, VersionedConfig<'a, T>>,
+}
+
+#[derive(Deserialize)]
+struct ProfilesDocument {
+    active: String,
+    profiles: BTreeMap<String, ConfigDocument>,
+}
+
+impl<T: Eq + Hash + Clone + DeserializeOwned> HotkeyProfiles<T> {
+    /// Load all profiles, upgrading each of them to the newest version first.
+    pub fn load_from_str_with_migrations(
+        contents: &str,
+        format: ConfigFormat,
+        migrations: &HotkeyMigrations,
+    ) -> Result<Self, PersistenceError> {
+        let document: ProfilesDocument = parse_str(contents, format)?;
+        if !document.profiles.contains_key(&document.active) {
+            return Err(PersistenceError::Parse(format!(
+                "active hotkey profile {} does not exist",
+                document.active
+            )));
+        }
+        let profiles = document
+            .profiles
+            .into_iter()
+            .map(|(name, mut config)| {
+                migrations.migrate(&mut config);
+                (name, config.into_config())
+            })
+            .collect();
+        Ok(Self {
+            profiles,
+            active: document.active,
+            active_changed: false,
+            modified: false,
+        })
+    }
+}
+
+impl<T: Eq + Hash + Clone + Serialize> HotkeyProfiles<T> {
+    pub fn save_to_string(&self, format: ConfigFormat) -> Result<String, PersistenceError> {
+        self.serialize_with_version(format, None)
+    }
+
+    /// Save every profile as the newest version of the migrations.
+    pub fn save_to_string_with_migrations(
+        &self,
+        format: ConfigFormat,
+        migrations: &HotkeyMigrations,
+    ) -> Result<String, PersistenceError> {
+        self.serialize_with_version(format, Some(migrations.current_version()))
+    }
+
+    fn serialize_with_version(
+        &self,
+        format: ConfigFormat,
+        version: Option<u32>,
+    ) -> Result<String, PersistenceError> {
+        let profiles = SerializedProfiles {
+            active: &self.active,
+            profiles: self
+                .profiles
+                .iter()
+                .map(|(name, config)| {
+                    (
+                        name,
+                        VersionedConfig(config, version.unwrap_or(config.version)),
+                    )
+                })
+                .collect(),
+        };
+        to_string(&profiles, format)
+    }
+}
+
+pub(crate) fn apply_profile_system<T: Sync + Send + 'static + Eq + Hash + Clone>(
+    mut profiles: ResMut<HotkeyProfiles<T>>,
+    mut hotkey_states: ResMut<HotkeyStates<T>>,
+    listener_config: Option<ResMut<HotkeyConfig<T>>>,
+    persistence: Option<Res<HotkeyPersistence<T>>>,
+) {
+    if profiles.active_changed {
+        profiles.active_changed = false;
+        let config = profiles.active().clone();
+        if let Some(mut listener_config) = listener_config {
+            *listener_config = config.clone();
+        }
+        hotkey_states.set_config(config);
+    }
+    if profiles.modified {
+        profiles.modified = false;
+        if let Some(persistence) = persistence {
+            if let Err(err) = persistence.save_profiles(&profiles) {
+                error!(
+                    "Failed to save hotkey profiles to {}: {}",
+                    persistence.path().display(),
+                    err
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::KeyCode;
+
+    use super::*;
+
+    fn profiles() -> HotkeyProfiles<String> {
+        let mut config = HotkeyConfig::empty();
+        config.insert_normal("Jump".to_string(), KeyCode::Space);
+        HotkeyProfiles::new("Alice", config)
+    }
+
+    #[test]
+    fn duplicate() {
+        let mut profiles = profiles();
+        profiles.duplicate("Alice", "Bob").unwrap();
+        assert_eq!(profiles.names().collect::<Vec<_>>(), ["Alice", "Bob"]);
+        assert_eq!(profiles.get("Bob").unwrap().map, profiles.active().map);
+        assert_eq!(
+            profiles.duplicate("Alice", "Bob"),
+            Err(ProfileError::AlreadyExists("Bob".into()))
+        );
+        assert_eq!(
+            profiles.duplicate("Carol", "Dave"),
+            Err(ProfileError::NotFound("Carol".into()))
+        );
+    }
+
+    #[test]
+    fn rename_active_profile() {
+        let mut profiles = profiles();
+        profiles.rename("Alice", "Carol").unwrap();
+        assert_eq!(profiles.active_name(), "Carol");
+        assert!(!profiles.contains("Alice"));
+        // Renaming to the same name changes nothing.
+        profiles.modified = false;
+        profiles.rename("Carol", "Carol").unwrap();
+        assert!(!profiles.modified);
+    }
+
+    #[test]
+    fn rename_errors() {
+        let mut profiles = profiles();
+        profiles.duplicate("Alice", "Bob").unwrap();
+        assert_eq!(
+            profiles.rename("Alice", "Bob"),
+            Err(ProfileError::AlreadyExists("Bob".into()))
+        );
+        assert_eq!(
+            profiles.rename("Carol", "Dave"),
+            Err(ProfileError::NotFound("Carol".into()))
+        );
+        assert_eq!(
+            profiles.rename("Carol", "Carol"),
+            Err(ProfileError::NotFound("Carol".into()))
+        );
+    }
+
+    #[test]
+    fn delete() {
+        let mut profiles = profiles();
+        profiles.duplicate("Alice", "Bob").unwrap();
+        assert_eq!(
+            profiles.delete("Alice"),
+            Err(ProfileError::Active("Alice".into()))
+        );
+        profiles.delete("Bob").unwrap();
+        assert_eq!(
+            profiles.delete("Bob"),
+            Err(ProfileError::NotFound("Bob".into()))
+        );
+        assert_eq!(profiles.names().collect::<Vec<_>>(), ["Alice"]);
+    }
+
+    #[test]
+    fn set_active() {
+        let mut profiles = profiles();
+        profiles.duplicate("Alice", "Bob").unwrap();
+        profiles.set_active("Bob").unwrap();
+        assert_eq!(profiles.active_name(), "Bob");
+        assert!(profiles.active_changed);
+        assert_eq!(
+            profiles.set_active("Carol"),
+            Err(ProfileError::NotFound("Carol".into()))
+        );
+    }
+
+    #[test]
+    fn formats_round_trip() {
+        let mut profiles = profiles();
+        profiles.duplicate("Alice", "Bob").unwrap();
+        profiles.set_active("Bob").unwrap();
+        let migrations =
+            HotkeyMigrations::new().add_migration(|document| document.rename_action("Hop", "Jump"));
+        for format in ConfigFormat::ALL {
+            let saved = profiles
+                .save_to_string_with_migrations(*format, &migrations)
+                .unwrap();
+            let loaded: HotkeyProfiles<String> =
+                HotkeyProfiles::load_from_str_with_migrations(&saved, *format, &migrations)
+                    .unwrap();
+            assert_eq!(loaded.active_name(), "Bob", "{:?}", format);
+            assert_eq!(loaded.get("Alice").unwrap().map, profiles.active().map);
+            assert_eq!(loaded.active().version(), 1, "{:?}", format);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use serde::de::value::Error;
+use serde::de::value::StrDeserializer;
+use serde::de::DeserializeOwned;
+use serde::de::IntoDeserializer;
+use serde::ser::Error as _;
+use serde::ser::Impossible;
+use serde::Serialize;
+use serde::Serializer;
+
+// Not every format supports enums as map keys (toml doesn't), so the actions
+// are converted to plain strings before serializing the config.
+pub(crate) fn action_to_string<T: Serialize>(action: &T) -> Result<String, Error> {
+    action.serialize(ActionNameSerializer)
+}
+
+pub(crate) fn action_from_str<T: DeserializeOwned>(name: &str) -> Result<T, Error> {
+    let deserializer: StrDeserializer<Error> = name.into_deserializer();
+    T::deserialize(deserializer)
+}
+
+struct ActionNameSerializer;
+
+fn unsupported() -> Error {
+    Error::custom("actions must be unit variants or strings")
+}
+
+impl Serializer for ActionNameSerializer {
+    type Ok = String;
+    type Error = Error;
+    type SerializeSeq = Impossible<String, Error>;
+    type SerializeTuple = Impossible<String, Error>;
+    type SerializeTupleStruct = Impossible<String, Error>;
+    type SerializeTupleVariant = Impossible<String, Error>;
+    type SerializeMap = Impossible<String, Error>;
+    type SerializeStruct = Impossible<String, Error>;
+    type SerializeStructVariant = Impossible<String, Error>;
+
+    fn serialize_bool(self, _v: bool) -> Result<String, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<String, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<String, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<String, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<String, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<String, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<String, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<String, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<String, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_char(self, v: char) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_none(self) -> Result<String, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_some<V: ?Sized + Serialize>(self, _value: &V) -> Result<String, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_unit(self) -> Result<String, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<String, Error> {
+        Ok(name.into())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String, Error> {
+        Ok(variant.into())
+    }
+
+    fn serialize_newtype_struct<V: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &V,
+    ) -> Result<String, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<V: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &V,
+    ) -> Result<String, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(unsupported())
+    }
+}
@@ -0,0 +1,17 @@
+/// Set while a text field has focus, so that typing doesn't trigger
+/// gameplay actions. All actions except those allowed with
+/// `HotkeyPlugin::allow_in_text_input` are released until it is unset.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TextInputFocus {
+    focused: bool,
+}
+
+impl TextInputFocus {
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+}
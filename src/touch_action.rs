@@ -0,0 +1,12 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum TouchAction {
+    /// A touch anywhere in the window.
+    Tap,
+    /// A touch within the `TouchRegion` of this name in the `TouchSettings`.
+    Region(String),
+    /// Exactly this many fingers touching the screen.
+    Fingers(u8),
+}
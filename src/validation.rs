@@ -0,0 +1,239 @@
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::hash::Hash;
+
+use crate::action::Action;
+use crate::hotkey::Hotkey;
+use crate::hotkey_config::HotkeyConfig;
+
+/// Additional requirements that `HotkeyConfig::validate_with` checks for.
+#[derive(Clone, Debug)]
+pub struct ValidationRules<T> {
+    required: Vec<T>,
+    reserved: Vec<Action>,
+}
+
+impl<T> Default for ValidationRules<T> {
+    fn default() -> Self {
+        Self {
+            required: vec![],
+            reserved: vec![],
+        }
+    }
+}
+
+impl<T> ValidationRules<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report the action if it has no hotkeys.
+    pub fn require(mut self, name: T) -> Self {
+        self.required.push(name);
+        self
+    }
+
+    /// Report any hotkey using this key, such as the cancel and
+    /// remove actions of the `HotkeyListener`.
+    pub fn reserve(mut self, action: Action) -> Self {
+        self.reserved.push(action);
+        self
+    }
+}
+
+/// The same hotkey bound to more than one action.
+#[derive(Clone, Debug)]
+pub struct HotkeyConflict<T> {
+    pub hotkey: Hotkey,
+    pub actions: Vec<T>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ValidationReport<T> {
+    /// Actions present in the config without any hotkeys.
+    pub unbound_actions: Vec<T>,
+    /// Hotkeys listed more than once for the same action.
+    pub duplicate_bindings: Vec<(T, Hotkey)>,
+    pub conflicts: Vec<HotkeyConflict<T>>,
+    pub reserved_bindings: Vec<(T, Hotkey)>,
+    /// Required actions which are missing or have no hotkeys.
+    pub missing_required: Vec<T>,
+}
+
+impl<T> ValidationReport<T> {
+    pub fn is_ok(&self) -> bool {
+        self.unbound_actions.is_empty()
+            && self.duplicate_bindings.is_empty()
+            && self.conflicts.is_empty()
+            && self.reserved_bindings.is_empty()
+            && self.missing_required.is_empty()
+    }
+}
+
+impl<T: Eq + Hash + Clone> HotkeyConfig<T> {
+    pub fn validate(&self) -> ValidationReport<T> {
+        self.validate_with(&ValidationRules::default())
+    }
+
+    pub fn validate_with(&self, rules: &ValidationRules<T>) -> ValidationReport<T> {
+        let mut report = ValidationReport {
+            unbound_actions: vec![],
+            duplicate_bindings: vec![],
+            conflicts: vec![],
+            reserved_bindings: vec![],
+            missing_required: vec![],
+        };
+        for (name, hotkeys) in self.iter() {
+            if hotkeys.iter().next().is_none() {
+                report.unbound_actions.push(name.clone());
+            }
+            for (num, hotkey) in hotkeys.iter().enumerate() {
+                if hotkeys.iter().take(num).any(|other| other == hotkey) {
+                    report
+                        .duplicate_bindings
+                        .push((name.clone(), hotkey.clone()));
+                }
+                if rules.reserved.contains(&hotkey.key) {
+                    report
+                        .reserved_bindings
+                        .push((name.clone(), hotkey.clone()));
+                }
+                let conflict = report
+                    .conflicts
+                    .iter_mut()
+                    .find(|conflict| conflict.hotkey == *hotkey);
+                match conflict {
+                    Some(conflict) => {
+                        if !conflict.actions.contains(name) {
+                            conflict.actions.push(name.clone());
+                        }
+                    }
+                    None => report.conflicts.push(HotkeyConflict {
+                        hotkey: hotkey.clone(),
+                        actions: vec![name.clone()],
+                    }),
+                }
+            }
+        }
+        report
+            .conflicts
+            .retain(|conflict| conflict.actions.len() > 1);
+        report.missing_required = rules
+            .required
+            .iter()
+            .filter(|name| {
+                self.get(name)
+                    .map(|hotkeys| hotkeys.iter().next().is_none())
+                    .unwrap_or(true)
+            })
+            .cloned()
+            .collect();
+        report
+    }
+}
+
+impl<T: Debug> Display for ValidationReport<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_ok() {
+            return write!(f, "no problems found");
+        }
+        for name in self.unbound_actions.iter() {
+            writeln!(f, "{:?} has no hotkeys", name)?;
+        }
+        for (name, hotkey) in self.duplicate_bindings.iter() {
+            writeln!(f, "{:?} is bound to {} more than once", name, hotkey)?;
+        }
+        for conflict in self.conflicts.iter() {
+            writeln!(
+                f,
+                "{} is bound to multiple actions: {:?}",
+                conflict.hotkey, conflict.actions
+            )?;
+        }
+        for (name, hotkey) in self.reserved_bindings.iter() {
+            writeln!(f, "{:?} is bound to reserved hotkey {}", name, hotkey)?;
+        }
+        for name in self.missing_required.iter() {
+            writeln!(f, "required action {:?} has no hotkeys", name)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::KeyCode;
+
+    use super::*;
+
+    fn config(bindings: &[(&str, &str)]) -> HotkeyConfig<String> {
+        let mut config = HotkeyConfig::empty();
+        for (name, hotkey) in bindings {
+            config.insert_parsed(name.to_string(), hotkey).unwrap();
+        }
+        config
+    }
+
+    #[test]
+    fn valid_config() {
+        let report = config(&[("Jump", "Space"), ("Duck", "S"), ("Save", "ctrl+S")]).validate();
+        assert!(report.is_ok(), "{}", report);
+        assert_eq!(report.to_string(), "no problems found");
+    }
+
+    #[test]
+    fn unbound_and_duplicate_bindings() {
+        let mut config = config(&[("Jump", "Space"), ("Jump", "Space")]);
+        config
+            .map
+            .insert("Duck".into(), crate::Hotkeys::new(vec![]));
+        let report = config.validate();
+        assert_eq!(report.unbound_actions, vec!["Duck"]);
+        assert_eq!(report.duplicate_bindings.len(), 1);
+        assert_eq!(report.duplicate_bindings[0].0, "Jump");
+        assert!(report.conflicts.is_empty());
+    }
+
+    #[test]
+    fn conflicts_ignore_modifier_order() {
+        let report = config(&[
+            ("Save", "ctrl+shift+S"),
+            ("SaveAs", "shift+ctrl+S"),
+            ("Duck", "S"),
+        ])
+        .validate();
+        assert_eq!(report.conflicts.len(), 1);
+        let mut actions = report.conflicts[0].actions.clone();
+        actions.sort_unstable();
+        assert_eq!(actions, vec!["Save", "SaveAs"]);
+    }
+
+    #[test]
+    fn reserved_and_required() {
+        let rules = ValidationRules::new()
+            .require("Jump".to_string())
+            .require("Pause".to_string())
+            .reserve(Action::Key(KeyCode::Escape));
+        let report = config(&[("Jump", "Space"), ("Menu", "shift+Escape")]).validate_with(&rules);
+        assert_eq!(report.reserved_bindings.len(), 1);
+        assert_eq!(report.reserved_bindings[0].0, "Menu");
+        assert_eq!(report.missing_required, vec!["Pause"]);
+        assert!(!report.is_ok());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn shipped_config_is_valid() {
+        let config: HotkeyConfig<String> = HotkeyConfig::load_from_str(
+            include_str!("../assets/game.hotkeys.json"),
+            crate::persistence::ConfigFormat::Json,
+        )
+        .unwrap();
+        let rules = ValidationRules::new()
+            .require("Jump".to_string())
+            .require("Duck".to_string())
+            .reserve(Action::Key(KeyCode::Escape));
+        let report = config.validate_with(&rules);
+        assert!(report.is_ok(), "{}", report);
+    }
+}
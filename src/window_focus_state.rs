@@ -1,30 +1,24 @@
 use bevy::input::Input;
 use bevy::prelude::KeyCode;
 use bevy::prelude::MouseButton;
-use bevy::window::Window;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub(super) enum WindowFocusState {
+    #[default]
     InFocus,
     OutOfFocusOrWaitingForInputRelease,
 }
 
-impl Default for WindowFocusState {
-    fn default() -> Self {
-        WindowFocusState::InFocus
-    }
-}
-
 impl WindowFocusState {
     pub fn interaction_allowed(
         &mut self,
-        window: &Window,
+        focused: bool,
         keyboard_input: &Input<KeyCode>,
         mouse_input: &Input<MouseButton>,
     ) -> bool {
         use WindowFocusState::*;
 
-        if window.is_focused() {
+        if focused {
             match self {
                 InFocus => true,
                 OutOfFocusOrWaitingForInputRelease